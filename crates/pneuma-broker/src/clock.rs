@@ -0,0 +1,124 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+
+/// Time source for the broker's backoff windows and escalation deadlines.
+/// [`SystemClock`] is the real one; [`MockClock`] only moves when told to,
+/// so tests can cross a backoff window without sleeping through it.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    /// Resolve once `now()` reaches `deadline`.
+    async fn sleep_until(&self, deadline: Instant);
+
+    async fn sleep(&self, duration: Duration) {
+        self.sleep_until(self.now() + duration).await;
+    }
+}
+
+/// Wall-clock time, with sleeps on the tokio timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep_until(&self, deadline: Instant) {
+        tokio::time::sleep_until(deadline.into()).await;
+    }
+}
+
+/// Clock that stands still until [`advance`](MockClock::advance)d; sleepers
+/// wake as soon as an advance carries it past their deadline.
+#[derive(Debug)]
+pub struct MockClock {
+    now: watch::Sender<Instant>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            now: watch::Sender::new(Instant::now()),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: Instant) {
+        let mut now = self.now.subscribe();
+        // The sender lives as long as `self`, so this only returns once reached.
+        let _ = now.wait_for(|now| *now >= deadline).await;
+    }
+}
+
+/// `deadline` on `clock` passed before the future completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline elapsed")]
+pub struct Elapsed;
+
+/// Like [`tokio::time::timeout_at`], with the deadline measured on `clock`.
+pub async fn timeout_at<F: Future>(clock: &dyn Clock, deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
+    tokio::select! {
+        biased;
+        output = future => Ok(output),
+        () = clock.sleep_until(deadline) => Err(Elapsed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{timeout_at, Clock, Elapsed, MockClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn mock_sleep_wakes_only_once_advanced_past_its_deadline() {
+        let clock = Arc::new(MockClock::new());
+        let start = clock.now();
+        let sleeper = tokio::spawn({
+            let clock = Arc::clone(&clock);
+            async move { clock.sleep(Duration::from_secs(30)).await }
+        });
+        // Let the sleeper take its deadline before time moves.
+        tokio::task::yield_now().await;
+
+        clock.advance(Duration::from_secs(29));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(1));
+        sleeper.await.unwrap();
+        assert_eq!(clock.now() - start, Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn timeout_at_fails_when_the_mock_deadline_passes() {
+        let clock = MockClock::new();
+        let deadline = clock.now() + Duration::from_secs(10);
+        assert_eq!(timeout_at(&clock, deadline, async { 7 }).await, Ok(7));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timeout_at(&clock, deadline, std::future::pending::<()>()).await, Err(Elapsed));
+    }
+}
@@ -0,0 +1,180 @@
+use serde_json::Value;
+
+use super::ConfidenceSignals;
+
+/// Turns the metadata JSON an engine's `navigate` returns into scorer inputs.
+///
+/// The broker scores every navigate through one of these, so an engine with a
+/// different (or richer) metadata schema only needs its own extractor.
+pub trait SignalExtractor: Send + Sync {
+    /// Missing or malformed fields should fall back to defaults rather than
+    /// fail; `page_id` is for logs.
+    fn extract(&self, meta_json: &str, page_id: u32) -> ConfidenceSignals;
+}
+
+/// Reads the Servo engine's navigate metadata, inferring a baseline from the
+/// title and overriding it with whatever the in-page probe reported. See
+/// [`signals_from_navigate_meta`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NavigateMetaExtractor;
+
+impl SignalExtractor for NavigateMetaExtractor {
+    fn extract(&self, meta_json: &str, page_id: u32) -> ConfidenceSignals {
+        signals_from_navigate_meta(meta_json, page_id)
+    }
+}
+
+/// Derive scorer inputs from the metadata JSON an engine's `navigate` returns.
+/// Missing or malformed fields fall back to defaults; `page_id` is only used for logs.
+pub fn signals_from_navigate_meta(meta_json: &str, page_id: u32) -> ConfidenceSignals {
+    let sampled_at_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    let mut signals = ConfidenceSignals {
+        sampled_at_ms,
+        ..Default::default()
+    };
+
+    let meta: Value = match serde_json::from_str(meta_json) {
+        Ok(value) => value,
+        Err(error) => {
+            tracing::debug!(
+                target: "pneuma_broker",
+                page_id,
+                error = %error,
+                "failed to parse navigate metadata JSON"
+            );
+            return signals;
+        }
+    };
+
+    let Some(object) = meta.as_object() else {
+        tracing::debug!(
+            target: "pneuma_broker",
+            page_id,
+            "navigate metadata was not a JSON object"
+        );
+        return signals;
+    };
+
+    let ok = object.get("ok").and_then(Value::as_bool).unwrap_or(false);
+    if ok {
+        signals.first_paint_ms = Some(600);
+    }
+
+    let title = object
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim();
+    signals.title = title.to_string();
+    if !title.is_empty() {
+        signals.paint_element_count = 24;
+        signals.dom_element_count = 32;
+        signals.dom_depth_max = 6;
+        signals.body_text_length = std::cmp::max(title.len() * 12, 64);
+        signals.js_execution_time_ms = 250;
+    }
+
+    if let Some(value) = parse_u64(object, "first_paint_ms") {
+        signals.first_paint_ms = Some(value);
+    }
+    if let Some(value) = parse_usize(object, "paint_element_count") {
+        signals.paint_element_count = value;
+    }
+    if let Some(value) = parse_usize(object, "dom_element_count") {
+        signals.dom_element_count = value;
+    }
+    if let Some(value) = parse_usize(object, "dom_depth_max") {
+        signals.dom_depth_max = value;
+    }
+    if let Some(value) = parse_usize(object, "body_text_length") {
+        signals.body_text_length = value;
+    }
+
+    if let Some(value) = parse_u32(object, "js_errors") {
+        signals.js_errors = value;
+    }
+    if let Some(value) = parse_u32(object, "unhandled_promise_rejections") {
+        signals.unhandled_promise_rejections = value;
+    }
+    if let Some(value) = parse_u32(object, "console_error_count") {
+        signals.console_error_count = value;
+    }
+    if let Some(value) = parse_u64(object, "js_execution_time_ms") {
+        signals.js_execution_time_ms = value;
+    }
+    if let Some(value) = parse_u64(object, "dom_interactive_ms") {
+        signals.dom_interactive_ms = Some(value);
+    }
+    if let Some(value) = parse_u32(object, "failed_resource_count") {
+        signals.failed_resource_count = value;
+    }
+    if let Some(value) = parse_u32(object, "cors_violations") {
+        signals.cors_violations = value;
+    }
+    if let Some(value) = parse_u32(object, "pending_requests_at_sample") {
+        signals.pending_requests_at_sample = value;
+    }
+    if let Some(value) = parse_u32(object, "css_parse_failures") {
+        signals.css_parse_failures = value;
+    }
+    signals.http_status = parse_u64(object, "http_status")
+        .and_then(|status| u16::try_from(status).ok())
+        .filter(|status| *status > 0);
+    signals.content_type = object
+        .get("content_type")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|content_type| !content_type.is_empty())
+        .map(str::to_string);
+    if let Some(value) = object.get("meta_refresh").and_then(Value::as_bool) {
+        signals.meta_refresh = value;
+    }
+    signals.script_srcs = parse_strings(object, "script_srcs");
+    signals.form_actions = parse_strings(object, "form_actions");
+    signals.redirect_loop = parse_strings(object, "redirect_loop");
+    if let Some(value) = object.get("navigation_timings").filter(|value| value.is_object()) {
+        match serde_json::from_value(value.clone()) {
+            Ok(timings) => signals.navigation_timings = Some(timings),
+            Err(error) => {
+                tracing::debug!(
+                    target: "pneuma_broker",
+                    page_id,
+                    error = %error,
+                    "failed to parse navigation timings"
+                );
+            }
+        }
+    }
+
+    signals
+}
+
+fn parse_u32(object: &serde_json::Map<String, Value>, key: &str) -> Option<u32> {
+    object
+        .get(key)
+        .and_then(Value::as_u64)
+        .map(|value| value.min(u32::MAX as u64) as u32)
+}
+
+fn parse_u64(object: &serde_json::Map<String, Value>, key: &str) -> Option<u64> {
+    object.get(key).and_then(Value::as_u64)
+}
+
+fn parse_strings(object: &serde_json::Map<String, Value>, key: &str) -> Vec<String> {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn parse_usize(object: &serde_json::Map<String, Value>, key: &str) -> Option<usize> {
+    object
+        .get(key)
+        .and_then(Value::as_u64)
+        .map(|value| value.min(usize::MAX as u64) as usize)
+}
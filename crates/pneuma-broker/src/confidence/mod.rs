@@ -1,5 +1,11 @@
+pub mod extractor;
 pub mod scorer;
 pub mod signals;
 
-pub use scorer::{ConfidenceReport, ConfidenceScorer, EngineDecision, FailureReason};
-pub use signals::ConfidenceSignals;
+pub use extractor::{signals_from_navigate_meta, NavigateMetaExtractor, SignalExtractor};
+pub use scorer::{
+    ChallengeMarkers, ConfidenceReport, ConfidenceScorer, DecisionBand, EngineDecision, EscalationMode,
+    EscalationOverride, FailureReason, HttpErrorAction, PaintCurve,
+    DEFAULT_DOM_INTERACTIVE_THRESHOLD_MS,
+};
+pub use signals::{ConfidenceSignals, ConfidenceSignalsBuilder, InvalidSignals, NavigationTimings};
@@ -1,6 +1,23 @@
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
 use super::ConfidenceSignals;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Weights of the sub-scores in `overall`; they sum to 1.
+const PAINT_WEIGHT: f32 = 0.35;
+const DOM_WEIGHT: f32 = 0.30;
+const JS_WEIGHT: f32 = 0.25;
+const NETWORK_WEIGHT: f32 = 0.10;
+/// Every sub-score of a document DOM-based scoring does not apply to.
+const NEUTRAL_SCORE: f32 = 0.5;
+/// `domInteractive` past which a page counts as stalled, unless
+/// configured with [`ConfidenceScorer::with_dom_interactive_threshold_ms`].
+pub const DEFAULT_DOM_INTERACTIVE_THRESHOLD_MS: u64 = 10_000;
+/// Taken off the JS score of a page whose parsing stalled.
+const DOM_INTERACTIVE_STALL_PENALTY: f32 = 0.3;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum FailureReason {
     ZeroPaint,
     /// SPA pre-hydration stall — page shell loaded but JS hydration did not complete.
@@ -10,16 +27,142 @@ pub enum FailureReason {
     NetworkStarvation { failed: u32 },
     CssLayoutCollapse,
     SlowExecution { ms: u64 },
+    /// Anti-bot interstitial ("Just a moment..."): the DOM is complete but is
+    /// not the requested content. `marker` is the evidence that matched.
+    ChallengePage { marker: String },
+    /// The page bounced between these URLs and never settled.
+    RedirectLoop { urls: Vec<String> },
+    /// The main document came back 4xx/5xx: a rendered error page.
+    HttpError { status: u16 },
+    /// The navigate's options asked for escalation (`"escalation": "force"`).
+    Forced,
+}
+
+/// What a [`FailureReason::HttpError`] decides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpErrorAction {
+    /// Escalate like any other failure; some servers only refuse some engines.
+    #[default]
+    Escalate,
+    /// Stay on the primary and only report the reason, for callers that
+    /// treat error statuses themselves (another engine gets the same 404).
+    Report,
+}
+
+/// Per-navigate escalation mode, the `"escalation"` key of `opts_json`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscalationMode {
+    /// Let the scorer decide.
+    #[default]
+    Auto,
+    /// Stay on the current engine whatever the score.
+    Never,
+    /// Escalate whatever the score.
+    Force,
+}
+
+/// Scoring overrides a single navigate's `opts_json` can carry, e.g.
+/// `{"escalation": "never"}` for a known-sparse page or `{"threshold": 0.4}`.
+/// `threshold` replaces the scorer's threshold and decision band for that
+/// navigate; the failure checks still apply unless `escalation` is `never`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EscalationOverride {
+    pub escalation: EscalationMode,
+    pub threshold: Option<f32>,
 }
 
+impl EscalationOverride {
+    /// Parse the override keys of `opts_json`, rejecting only malformed
+    /// values; other keys are ignored and a non-object means no override.
+    pub fn parse(opts_json: &str) -> anyhow::Result<Self> {
+        let value = match serde_json::from_str::<serde_json::Value>(opts_json) {
+            Ok(value @ serde_json::Value::Object(_)) => value,
+            _ => return Ok(Self::default()),
+        };
+        let overrides: Self = serde_json::from_value(value).context("invalid escalation options")?;
+        if let Some(threshold) = overrides.threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                bail!("invalid escalation options: threshold must be between 0 and 1, got {threshold}");
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Whether the scorer's own decision stands.
+    pub fn is_auto(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What identifies an anti-bot challenge page. Matching is case-insensitive
+/// substring matching.
 #[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeMarkers {
+    /// Matched against the document title.
+    pub titles: Vec<String>,
+    /// Matched against external script URLs and form actions, which is how
+    /// challenge widgets show up in the DOM.
+    pub resources: Vec<String>,
+    /// A meta-refresh page with less body text than this is treated as a
+    /// challenge. `0` disables the check.
+    pub meta_refresh_max_body_text: usize,
+}
+
+impl Default for ChallengeMarkers {
+    fn default() -> Self {
+        Self {
+            titles: vec![
+                "just a moment".into(),
+                "attention required".into(),
+                "checking your browser".into(),
+                "ddos-guard".into(),
+                "access denied".into(),
+            ],
+            resources: vec![
+                "/cdn-cgi/challenge-platform/".into(),
+                "challenges.cloudflare.com".into(),
+                "captcha-delivery.com".into(),
+                "/_incapsula_resource".into(),
+                "perimeterx.net".into(),
+            ],
+            meta_refresh_max_body_text: 200,
+        }
+    }
+}
+
+impl ChallengeMarkers {
+    /// The first marker `signals` match, described for logs and reports.
+    pub fn detect(&self, signals: &ConfidenceSignals) -> Option<String> {
+        let contains = |haystack: &str, needle: &str| {
+            !needle.is_empty() && haystack.to_lowercase().contains(&needle.to_lowercase())
+        };
+        if let Some(marker) = self.titles.iter().find(|marker| contains(&signals.title, marker)) {
+            return Some(format!("title:{marker}"));
+        }
+        for resource in signals.script_srcs.iter().chain(&signals.form_actions) {
+            if let Some(marker) = self.resources.iter().find(|marker| contains(resource, marker)) {
+                return Some(format!("resource:{marker}"));
+            }
+        }
+        if signals.meta_refresh && signals.body_text_length < self.meta_refresh_max_body_text {
+            return Some("meta_refresh".into());
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", content = "detail", rename_all = "snake_case")]
 pub enum EngineDecision {
     StayOnServo,
     EscalateToLadybird(FailureReason),
     RetryWithPatches(Vec<String>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ConfidenceReport {
     pub paint_score: f32,
     pub dom_score: f32,
@@ -28,11 +171,192 @@ pub struct ConfidenceReport {
     pub overall: f32,
     pub failure_reason: Option<FailureReason>,
     pub decision: EngineDecision,
+    /// Each failure check in cascade order with its outcome and the values it
+    /// compared, e.g. "zero_paint: clear (paint 0.84)", ending at the first
+    /// one that fired. Shows near-misses when tuning the cascade.
+    pub decision_trace: Vec<String>,
+    /// Type of a non-HTML document, whose scores are neutral and which never
+    /// escalates on its own; absent for HTML.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+impl ConfidenceReport {
+    /// One or two sentences for logs and `pneuma probe`: the decision, what
+    /// dominated it (the failure reason, else the lowest sub-score) and each
+    /// sub-score's weighted contribution to `overall`, e.g.
+    /// "Escalating: paint score 0.00 (no first-paint observed) dominated the
+    /// decision. Overall 0.59 = paint 0.00 + dom 0.24 + js 0.25 + network 0.10."
+    pub fn explain(&self) -> String {
+        let action = match &self.decision {
+            EngineDecision::StayOnServo => "Staying on Servo",
+            EngineDecision::EscalateToLadybird(_) => "Escalating",
+            EngineDecision::RetryWithPatches(_) => "Retrying with patches",
+        };
+        let factors = [
+            ("paint", self.paint_score, PAINT_WEIGHT),
+            ("dom", self.dom_score, DOM_WEIGHT),
+            ("js", self.js_score, JS_WEIGHT),
+            ("network", self.network_score, NETWORK_WEIGHT),
+        ];
+        let score_of = |name: &str| factors.iter().find(|factor| factor.0 == name).map_or(0.0, |factor| factor.1);
+
+        let cause = match &self.failure_reason {
+            Some(FailureReason::ZeroPaint) => {
+                format!("paint score {:.2} (no first-paint observed) dominated the decision", score_of("paint"))
+            }
+            Some(FailureReason::SpaPrehyrationStall) => format!(
+                "dom score {:.2} (app shell never hydrated) dominated the decision",
+                score_of("dom")
+            ),
+            Some(FailureReason::JsCrashLoop { error_count }) => format!(
+                "js score {:.2} ({error_count} script errors) dominated the decision",
+                score_of("js")
+            ),
+            Some(FailureReason::SlowExecution { ms }) => format!(
+                "js score {:.2} ({ms} ms of script execution) dominated the decision",
+                score_of("js")
+            ),
+            Some(FailureReason::NetworkStarvation { failed }) => format!(
+                "network score {:.2} ({failed} failed requests) dominated the decision",
+                score_of("network")
+            ),
+            Some(FailureReason::CssLayoutCollapse) => {
+                "repeated stylesheet parse failures (layout collapse) dominated the decision".to_string()
+            }
+            Some(FailureReason::ChallengePage { marker }) => {
+                format!("an anti-bot challenge page ({marker}) dominated the decision")
+            }
+            Some(FailureReason::RedirectLoop { urls }) => {
+                format!("a redirect loop across {} URLs dominated the decision", urls.len())
+            }
+            Some(FailureReason::HttpError { status }) => {
+                format!("an HTTP {status} response dominated the decision")
+            }
+            Some(FailureReason::Forced) => "the navigate options forced escalation".to_string(),
+            None if self.decision == EngineDecision::EscalateToLadybird(FailureReason::Forced) => {
+                "the navigate options forced escalation".to_string()
+            }
+            None if self.content_type.is_some() => format!(
+                "{} is not HTML, so DOM-based scoring was skipped",
+                self.content_type.as_deref().unwrap_or_default()
+            ),
+            None => {
+                let (name, score, _) = factors
+                    .iter()
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .copied()
+                    .unwrap_or(factors[0]);
+                let verdict = match self.decision {
+                    EngineDecision::EscalateToLadybird(_) => "overall score too low",
+                    _ => "no failure detected",
+                };
+                format!("{verdict}; {name} score {score:.2} was the weakest factor")
+            }
+        };
+        let contributions = factors
+            .iter()
+            .map(|(name, score, weight)| format!("{name} {:.2}", score * weight))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        format!("{action}: {cause}. Overall {:.2} = {contributions}.", self.overall)
+    }
+}
+
+/// Hysteresis around the escalation threshold: scores below `escalate_below`
+/// escalate, scores at or above `stay_at` stay, and scores in between keep
+/// the page's previous decision so borderline pages do not flip-flop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecisionBand {
+    pub escalate_below: f32,
+    pub stay_at: f32,
+}
+
+impl Default for DecisionBand {
+    fn default() -> Self {
+        Self {
+            escalate_below: 0.55,
+            stay_at: 0.65,
+        }
+    }
+}
+
+/// How first-paint time and the painted element count map to the paint score,
+/// once something has painted (no paint scores 0.0, no painted elements 0.1).
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaintCurve {
+    /// 0.3 past `very_slow_ms`, 0.6 past `slow_ms`, otherwise 0.6 rising to
+    /// 1.0 as the element count reaches `full_element_count`.
+    Step {
+        slow_ms: u64,
+        very_slow_ms: u64,
+        full_element_count: usize,
+    },
+    /// The element-count score of `Step`, scaled smoothly down towards 0.3
+    /// as first paint passes `midpoint_ms`; `steepness_ms` sets how sharply.
+    /// Suits image-heavy pages that paint slowly but correctly.
+    Logistic {
+        midpoint_ms: u64,
+        steepness_ms: u64,
+        full_element_count: usize,
+    },
+}
+
+impl Default for PaintCurve {
+    fn default() -> Self {
+        Self::Step {
+            slow_ms: 3000,
+            very_slow_ms: 8000,
+            full_element_count: 100,
+        }
+    }
+}
+
+impl PaintCurve {
+    /// Score for a page that painted `element_count > 0` elements, first at `first_paint_ms`.
+    pub fn score(&self, first_paint_ms: u64, element_count: usize) -> f32 {
+        let by_elements = |full: usize| (element_count as f32 / full.max(1) as f32).min(1.0) * 0.4 + 0.6;
+        match *self {
+            Self::Step {
+                slow_ms,
+                very_slow_ms,
+                full_element_count,
+            } => {
+                if first_paint_ms > very_slow_ms {
+                    0.3
+                } else if first_paint_ms > slow_ms {
+                    0.6
+                } else {
+                    by_elements(full_element_count)
+                }
+            }
+            Self::Logistic {
+                midpoint_ms,
+                steepness_ms,
+                full_element_count,
+            } => {
+                let excess = (first_paint_ms as f64 - midpoint_ms as f64) / steepness_ms.max(1) as f64;
+                let speed = (1.0 / (1.0 + excess.exp())) as f32;
+                0.3 + (by_elements(full_element_count) - 0.3) * speed
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ConfidenceScorer {
+    /// Single cut-off used when no [`DecisionBand`] is set, or when a score
+    /// falls inside the band and there is no previous decision to keep.
     pub escalation_threshold: f32,
+    pub band: Option<DecisionBand>,
+    pub challenge_markers: ChallengeMarkers,
+    pub paint_curve: PaintCurve,
+    pub http_errors: HttpErrorAction,
+    /// `domInteractive` above which the JS score is penalised.
+    pub dom_interactive_threshold_ms: u64,
+    /// Whether a slow time to first byte lowers the network score. Off by
+    /// default: a slow origin says little about whether the engine coped.
+    pub ttfb_penalty: bool,
 }
 
 impl Default for ConfidenceScorer {
@@ -45,25 +369,82 @@ impl ConfidenceScorer {
     pub fn new() -> Self {
         Self {
             escalation_threshold: 0.60,
+            band: None,
+            challenge_markers: ChallengeMarkers::default(),
+            paint_curve: PaintCurve::default(),
+            http_errors: HttpErrorAction::default(),
+            dom_interactive_threshold_ms: DEFAULT_DOM_INTERACTIVE_THRESHOLD_MS,
+            ttfb_penalty: false,
         }
     }
 
     pub fn with_threshold(threshold: f32) -> Self {
         Self {
             escalation_threshold: threshold,
+            ..Self::new()
         }
     }
 
+    pub fn with_band(mut self, band: DecisionBand) -> Self {
+        assert!(
+            band.escalate_below <= band.stay_at,
+            "decision band is inverted: escalate_below {} > stay_at {}",
+            band.escalate_below,
+            band.stay_at
+        );
+        self.band = Some(band);
+        self
+    }
+
+    pub fn with_challenge_markers(mut self, markers: ChallengeMarkers) -> Self {
+        self.challenge_markers = markers;
+        self
+    }
+
+    pub fn with_paint_curve(mut self, curve: PaintCurve) -> Self {
+        self.paint_curve = curve;
+        self
+    }
+
+    pub fn with_http_errors(mut self, action: HttpErrorAction) -> Self {
+        self.http_errors = action;
+        self
+    }
+
+    pub fn with_dom_interactive_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.dom_interactive_threshold_ms = threshold_ms;
+        self
+    }
+
+    pub fn with_ttfb_penalty(mut self, enabled: bool) -> Self {
+        self.ttfb_penalty = enabled;
+        self
+    }
+
     pub fn score(&self, signals: &ConfidenceSignals) -> ConfidenceReport {
+        self.score_with_previous(signals, None)
+    }
+
+    /// Like [`score`](Self::score), resolving scores inside the
+    /// [`DecisionBand`] toward `previous`, the page's last decision.
+    pub fn score_with_previous(
+        &self,
+        signals: &ConfidenceSignals,
+        previous: Option<&EngineDecision>,
+    ) -> ConfidenceReport {
+        if !signals.is_html() {
+            return Self::non_html_report(signals.content_type.clone().unwrap_or_default());
+        }
         let paint = self.score_paint(signals);
         let dom = self.score_dom(signals);
         let js = self.score_js(signals);
         let network = self.score_network(signals);
 
-        let overall = paint * 0.35 + dom * 0.30 + js * 0.25 + network * 0.10;
+        let overall = paint * PAINT_WEIGHT + dom * DOM_WEIGHT + js * JS_WEIGHT + network * NETWORK_WEIGHT;
 
-        let failure_reason = self.classify_failure(signals, paint, dom, js);
-        let decision = self.decide(overall, &failure_reason, signals);
+        let mut decision_trace = Vec::new();
+        let failure_reason = self.classify_failure(signals, paint, dom, js, &mut decision_trace);
+        let decision = self.decide(overall, &failure_reason, previous);
 
         ConfidenceReport {
             paint_score: paint,
@@ -73,16 +454,69 @@ impl ConfidenceScorer {
             overall,
             failure_reason,
             decision,
+            decision_trace,
+            content_type: None,
+        }
+    }
+
+    /// Paint, DOM and the failure cascade describe rendered HTML; a JSON or
+    /// image document gets neutral scores and stays put.
+    fn non_html_report(content_type: String) -> ConfidenceReport {
+        ConfidenceReport {
+            paint_score: NEUTRAL_SCORE,
+            dom_score: NEUTRAL_SCORE,
+            js_score: NEUTRAL_SCORE,
+            network_score: NEUTRAL_SCORE,
+            overall: NEUTRAL_SCORE,
+            failure_reason: None,
+            decision: EngineDecision::StayOnServo,
+            decision_trace: vec![format!("content_type: skipped ({content_type} is not HTML)")],
+            content_type: Some(content_type),
         }
     }
 
+    /// Like [`score_with_previous`](Self::score_with_previous), with one
+    /// navigate's [`EscalationOverride`] applied; the override is recorded at
+    /// the end of the decision trace.
+    pub fn score_with_override(
+        &self,
+        signals: &ConfidenceSignals,
+        previous: Option<&EngineDecision>,
+        overrides: &EscalationOverride,
+    ) -> ConfidenceReport {
+        let mut report = match overrides.threshold {
+            Some(threshold) => Self {
+                escalation_threshold: threshold,
+                band: None,
+                ..self.clone()
+            }
+            .score_with_previous(signals, previous),
+            None => self.score_with_previous(signals, previous),
+        };
+        if let Some(threshold) = overrides.threshold {
+            report.decision_trace.push(format!("override: threshold {threshold:.2}"));
+        }
+        match overrides.escalation {
+            EscalationMode::Auto => {}
+            EscalationMode::Never => {
+                report.decision = EngineDecision::StayOnServo;
+                report.decision_trace.push("override: escalation never".to_string());
+            }
+            EscalationMode::Force => {
+                if !matches!(report.decision, EngineDecision::EscalateToLadybird(_)) {
+                    report.decision = EngineDecision::EscalateToLadybird(FailureReason::Forced);
+                }
+                report.decision_trace.push("override: escalation force".to_string());
+            }
+        }
+        report
+    }
+
     fn score_paint(&self, signals: &ConfidenceSignals) -> f32 {
         match (signals.first_paint_ms, signals.paint_element_count) {
             (None, _) => 0.0,
             (_, 0) => 0.1,
-            (Some(ms), _) if ms > 8000 => 0.3,
-            (Some(ms), _) if ms > 3000 => 0.6,
-            (Some(_), count) => (count as f32 / 100.0).min(1.0) * 0.4 + 0.6,
+            (Some(ms), count) => self.paint_curve.score(ms, count),
         }
     }
 
@@ -101,6 +535,10 @@ impl ConfidenceScorer {
         score -= signals.unhandled_promise_rejections as f32 * 0.15;
         score -= signals.console_error_count as f32 * 0.05;
         score -= signals.js_errors as f32 * 0.10;
+        // Synchronous scripts held the parser back long enough to matter.
+        if signals.dom_interactive_ms.is_some_and(|ms| ms > self.dom_interactive_threshold_ms) {
+            score -= DOM_INTERACTIVE_STALL_PENALTY;
+        }
         score.max(0.0)
     }
 
@@ -108,7 +546,17 @@ impl ConfidenceScorer {
         let pending = (signals.pending_requests_at_sample as f32 * 0.05).min(0.3);
         let cors = (signals.cors_violations as f32 * 0.10).min(0.4);
         let failed = (signals.failed_resource_count as f32 * 0.03).min(0.2);
-        (1.0 - pending - cors - failed).max(0.0)
+        let ttfb = signals
+            .navigation_timings
+            .as_ref()
+            .filter(|_| self.ttfb_penalty)
+            .and_then(|timings| timings.ttfb_ms());
+        let slow_ttfb = match ttfb {
+            Some(ms) if ms > 5000 => 0.2,
+            Some(ms) if ms > 2000 => 0.1,
+            _ => 0.0,
+        };
+        (1.0 - pending - cors - failed - slow_ttfb).max(0.0)
     }
 
     fn classify_failure(
@@ -117,27 +565,63 @@ impl ConfidenceScorer {
         paint: f32,
         dom: f32,
         _js: f32,
+        trace: &mut Vec<String>,
     ) -> Option<FailureReason> {
-        if paint == 0.0 {
+        let mut check = |name: &str, fired: bool, detail: String| {
+            let outcome = if fired { "fired" } else { "clear" };
+            trace.push(format!("{name}: {outcome} ({detail})"));
+            fired
+        };
+
+        // A looping page's other signals describe whichever hop was sampled.
+        let hops = signals.redirect_loop.len();
+        if check("redirect_loop", hops > 0, format!("{hops} looping urls")) {
+            return Some(FailureReason::RedirectLoop {
+                urls: signals.redirect_loop.clone(),
+            });
+        }
+        // Challenge pages render fully, so check them before the quality signals.
+        let marker = self.challenge_markers.detect(signals);
+        let detail = marker.as_ref().map_or_else(|| "no marker".to_string(), |marker| format!("marker {marker}"));
+        if check("challenge_page", marker.is_some(), detail) {
+            return marker.map(|marker| FailureReason::ChallengePage { marker });
+        }
+        // Error pages render fine too; the status says they are not the content.
+        let status = signals.http_status.filter(|status| *status >= 400);
+        let detail = signals.http_status.map_or_else(|| "no status".to_string(), |status| format!("status {status}"));
+        if check("http_error", status.is_some(), detail) {
+            return status.map(|status| FailureReason::HttpError { status });
+        }
+        if check("zero_paint", paint == 0.0, format!("paint {paint:.2}")) {
             return Some(FailureReason::ZeroPaint);
         }
-        if dom <= 0.2 {
+        if check("spa_stall", dom <= 0.2, format!("dom {dom:.2}, fires at 0.20 or below")) {
             return Some(FailureReason::SpaPrehyrationStall);
         }
-        if signals.js_errors > 3 || signals.unhandled_promise_rejections > 2 {
+        let detail = format!(
+            "{} errors, fires above 3; {} rejections, fires above 2",
+            signals.js_errors, signals.unhandled_promise_rejections
+        );
+        if check("js_crash_loop", signals.js_errors > 3 || signals.unhandled_promise_rejections > 2, detail) {
             return Some(FailureReason::JsCrashLoop {
                 error_count: signals.js_errors,
             });
         }
-        if signals.failed_resource_count > 5 || signals.cors_violations > 2 {
+        let detail = format!(
+            "{} failed resources, fires above 5; {} cors violations, fires above 2",
+            signals.failed_resource_count, signals.cors_violations
+        );
+        if check("network_starvation", signals.failed_resource_count > 5 || signals.cors_violations > 2, detail) {
             return Some(FailureReason::NetworkStarvation {
                 failed: signals.failed_resource_count,
             });
         }
-        if signals.css_parse_failures > 3 {
+        let detail = format!("{} parse failures, fires above 3", signals.css_parse_failures);
+        if check("css_layout_collapse", signals.css_parse_failures > 3, detail) {
             return Some(FailureReason::CssLayoutCollapse);
         }
-        if signals.js_execution_time_ms > 5000 {
+        let detail = format!("{} ms, fires above 5000", signals.js_execution_time_ms);
+        if check("slow_execution", signals.js_execution_time_ms > 5000, detail) {
             return Some(FailureReason::SlowExecution {
                 ms: signals.js_execution_time_ms,
             });
@@ -149,20 +633,29 @@ impl ConfidenceScorer {
         &self,
         overall: f32,
         reason: &Option<FailureReason>,
-        _signals: &ConfidenceSignals,
+        previous: Option<&EngineDecision>,
     ) -> EngineDecision {
         match reason {
             Some(FailureReason::SpaPrehyrationStall) => {
                 return EngineDecision::EscalateToLadybird(FailureReason::SpaPrehyrationStall);
             }
+            Some(FailureReason::HttpError { .. }) if self.http_errors == HttpErrorAction::Report => {
+                return EngineDecision::StayOnServo;
+            }
             Some(reason) => return EngineDecision::EscalateToLadybird(reason.clone()),
             None => {}
         }
 
-        if overall >= self.escalation_threshold {
-            EngineDecision::StayOnServo
-        } else {
+        let escalate = match (self.band, previous) {
+            (Some(band), _) if overall < band.escalate_below => true,
+            (Some(band), _) if overall >= band.stay_at => false,
+            (Some(_), Some(previous)) => matches!(previous, EngineDecision::EscalateToLadybird(_)),
+            _ => overall < self.escalation_threshold,
+        };
+        if escalate {
             EngineDecision::EscalateToLadybird(FailureReason::ZeroPaint)
+        } else {
+            EngineDecision::StayOnServo
         }
     }
 }
@@ -241,6 +734,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn late_dom_interactive_lowers_js_score() {
+        let scorer = ConfidenceScorer::new();
+        let responsive = scorer.score(&ConfidenceSignals {
+            dom_interactive_ms: Some(1_800),
+            ..healthy_signals()
+        });
+        let slow = ConfidenceSignals {
+            dom_interactive_ms: Some(14_000),
+            ..healthy_signals()
+        };
+        let stalled = scorer.score(&slow);
+        assert_eq!(responsive.js_score, 1.0);
+        assert!((stalled.js_score - 0.7).abs() < 1e-6, "js score {}", stalled.js_score);
+        assert!(stalled.overall < responsive.overall);
+        assert_eq!(stalled.failure_reason, None, "a stall only weighs on the score");
+
+        let lenient = ConfidenceScorer::new().with_dom_interactive_threshold_ms(20_000).score(&slow);
+        assert_eq!(lenient.js_score, 1.0, "the threshold is configurable");
+    }
+
+    #[test]
+    fn slow_ttfb_lowers_network_score_when_enabled() {
+        let scorer = ConfidenceScorer::new().with_ttfb_penalty(true);
+        let fast = scorer.score(&healthy_signals());
+        let slow_signals = ConfidenceSignals {
+            navigation_timings: Some(crate::confidence::NavigationTimings {
+                request_start: Some(100),
+                response_start: Some(6100),
+                ..Default::default()
+            }),
+            ..healthy_signals()
+        };
+        let slow = scorer.score(&slow_signals);
+        assert_eq!(fast.network_score, 1.0);
+        assert!((slow.network_score - 0.8).abs() < f32::EPSILON);
+
+        let unpenalised = ConfidenceScorer::new().score(&slow_signals);
+        assert_eq!(unpenalised.network_score, 1.0, "the penalty is opt-in");
+    }
+
     #[test]
     fn custom_threshold_is_respected() {
         let scorer = ConfidenceScorer::with_threshold(0.95);
@@ -250,4 +784,416 @@ mod tests {
             EngineDecision::EscalateToLadybird(_)
         ));
     }
+
+    #[test]
+    fn decision_trace_lists_checks_up_to_the_one_that_fired() {
+        let scorer = ConfidenceScorer::new();
+        let report = scorer.score(&ConfidenceSignals {
+            first_paint_ms: None,
+            paint_element_count: 0,
+            ..healthy_signals()
+        });
+        assert_eq!(
+            report.decision_trace,
+            [
+                "redirect_loop: clear (0 looping urls)",
+                "challenge_page: clear (no marker)",
+                "http_error: clear (no status)",
+                "zero_paint: fired (paint 0.00)",
+            ]
+        );
+
+        let report = scorer.score(&ConfidenceSignals {
+            js_errors: 3,
+            ..healthy_signals()
+        });
+        let checks: Vec<&str> = report
+            .decision_trace
+            .iter()
+            .map(|entry| entry.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            checks,
+            [
+                "redirect_loop",
+                "challenge_page",
+                "http_error",
+                "zero_paint",
+                "spa_stall",
+                "js_crash_loop",
+                "network_starvation",
+                "css_layout_collapse",
+                "slow_execution",
+            ],
+            "a healthy page runs the whole cascade"
+        );
+        assert!(report.decision_trace.iter().all(|entry| entry.contains(": clear (")));
+        assert_eq!(
+            report.decision_trace[5], "js_crash_loop: clear (3 errors, fires above 3; 0 rejections, fires above 2)",
+            "near-misses show how close the check came"
+        );
+    }
+
+    #[test]
+    fn escalation_override_modes_against_identical_signals() {
+        let scorer = ConfidenceScorer::new();
+        let decide = |signals: &ConfidenceSignals, opts_json: &str| {
+            let overrides = EscalationOverride::parse(opts_json).unwrap();
+            scorer.score_with_override(signals, None, &overrides).decision
+        };
+
+        let healthy = healthy_signals();
+        assert_eq!(decide(&healthy, "{}"), EngineDecision::StayOnServo);
+        assert_eq!(decide(&healthy, r#"{"escalation":"auto"}"#), EngineDecision::StayOnServo);
+        assert_eq!(decide(&healthy, r#"{"escalation":"never"}"#), EngineDecision::StayOnServo);
+        assert_eq!(
+            decide(&healthy, r#"{"escalation":"force"}"#),
+            EngineDecision::EscalateToLadybird(FailureReason::Forced)
+        );
+        assert!(matches!(
+            decide(&healthy, r#"{"threshold":0.99}"#),
+            EngineDecision::EscalateToLadybird(_)
+        ));
+
+        let blank = ConfidenceSignals {
+            first_paint_ms: None,
+            paint_element_count: 0,
+            ..healthy_signals()
+        };
+        let zero_paint = EngineDecision::EscalateToLadybird(FailureReason::ZeroPaint);
+        assert_eq!(decide(&blank, "{}"), zero_paint);
+        assert_eq!(decide(&blank, r#"{"escalation":"never"}"#), EngineDecision::StayOnServo);
+        assert_eq!(decide(&blank, r#"{"escalation":"force"}"#), zero_paint, "the real reason is kept");
+        assert_eq!(decide(&blank, r#"{"threshold":0.1}"#), zero_paint, "failure checks still fire");
+
+        let report = scorer.score_with_override(
+            &healthy,
+            None,
+            &EscalationOverride::parse(r#"{"escalation":"force"}"#).unwrap(),
+        );
+        assert_eq!(report.decision_trace.last().unwrap(), "override: escalation force");
+        assert!(report.explain().contains("forced escalation"), "{}", report.explain());
+    }
+
+    #[test]
+    fn escalation_override_rejects_only_malformed_values() {
+        assert!(EscalationOverride::parse("").unwrap().is_auto());
+        assert!(EscalationOverride::parse(r#"{"ready":"body"}"#).unwrap().is_auto());
+        let overrides = EscalationOverride::parse(r#"{"escalation":"never","threshold":0.4}"#).unwrap();
+        assert_eq!(overrides.escalation, EscalationMode::Never);
+        assert_eq!(overrides.threshold, Some(0.4));
+        assert!(EscalationOverride::parse(r#"{"escalation":"always"}"#).is_err());
+        assert!(EscalationOverride::parse(r#"{"threshold":"high"}"#).is_err());
+        assert!(EscalationOverride::parse(r#"{"threshold":1.5}"#).is_err());
+    }
+
+    #[test]
+    fn report_serializes_decision_and_reason() {
+        let report = ConfidenceScorer::new().score(&ConfidenceSignals {
+            first_paint_ms: None,
+            ..healthy_signals()
+        });
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["decision"]["action"], "escalate_to_ladybird");
+        assert_eq!(json["decision"]["detail"]["kind"], "zero_paint");
+        assert_eq!(json["failure_reason"]["kind"], "zero_paint");
+    }
+
+    fn challenge_signals() -> ConfidenceSignals {
+        // A typical interstitial: fully painted, modest DOM, healthy JS.
+        ConfidenceSignals {
+            title: "Just a moment...".into(),
+            script_srcs: vec!["/cdn-cgi/challenge-platform/h/b/orchestrate/chl_page/v1".into()],
+            form_actions: vec!["/?__cf_chl_f_tk=abc".into()],
+            ..healthy_signals()
+        }
+    }
+
+    #[test]
+    fn challenge_page_escalates_despite_healthy_scores() {
+        let report = ConfidenceScorer::new().score(&challenge_signals());
+        assert!(report.overall >= 0.60, "challenge pages score well: {}", report.overall);
+        assert_eq!(
+            report.failure_reason,
+            Some(FailureReason::ChallengePage {
+                marker: "title:just a moment".into()
+            })
+        );
+        assert!(matches!(
+            report.decision,
+            EngineDecision::EscalateToLadybird(FailureReason::ChallengePage { .. })
+        ));
+    }
+
+    #[test]
+    fn non_html_documents_get_neutral_scores_and_stay() {
+        let scorer = ConfidenceScorer::new();
+        let json = ConfidenceSignals {
+            content_type: Some("application/json".into()),
+            first_paint_ms: None,
+            dom_element_count: 2,
+            ..Default::default()
+        };
+        let report = scorer.score(&json);
+        assert_eq!(report.decision, EngineDecision::StayOnServo);
+        assert_eq!(report.failure_reason, None);
+        assert_eq!(report.overall, NEUTRAL_SCORE);
+        assert_eq!(report.content_type.as_deref(), Some("application/json"));
+        assert!(
+            report.explain().starts_with("Staying on Servo: application/json is not HTML"),
+            "{}",
+            report.explain()
+        );
+
+        // The same blank document as HTML, or of unknown type, escalates.
+        for content_type in [Some("application/xhtml+xml; charset=utf-8"), Some("TEXT/HTML"), None] {
+            let html = ConfidenceSignals {
+                content_type: content_type.map(str::to_string),
+                ..json.clone()
+            };
+            let report = scorer.score(&html);
+            assert_eq!(report.decision, EngineDecision::EscalateToLadybird(FailureReason::ZeroPaint));
+            assert_eq!(report.content_type, None);
+        }
+    }
+
+    #[test]
+    fn redirect_loop_escalates() {
+        let signals = ConfidenceSignals {
+            redirect_loop: vec!["https://a.example/".into(), "https://b.example/".into()],
+            ..healthy_signals()
+        };
+        let report = ConfidenceScorer::new().score(&signals);
+        assert_eq!(
+            report.decision,
+            EngineDecision::EscalateToLadybird(FailureReason::RedirectLoop {
+                urls: signals.redirect_loop.clone()
+            })
+        );
+    }
+
+    #[test]
+    fn http_error_status_is_a_failure_reason() {
+        let error_page = ConfidenceSignals {
+            http_status: Some(500),
+            ..healthy_signals()
+        };
+        let report = ConfidenceScorer::new().score(&error_page);
+        assert!(report.overall >= 0.60, "the error page itself renders fine");
+        assert_eq!(report.failure_reason, Some(FailureReason::HttpError { status: 500 }));
+        assert_eq!(
+            report.decision,
+            EngineDecision::EscalateToLadybird(FailureReason::HttpError { status: 500 })
+        );
+        assert!(report.explain().contains("HTTP 500"), "{}", report.explain());
+
+        let report = ConfidenceScorer::new()
+            .with_http_errors(HttpErrorAction::Report)
+            .score(&error_page);
+        assert_eq!(report.failure_reason, Some(FailureReason::HttpError { status: 500 }));
+        assert_eq!(report.decision, EngineDecision::StayOnServo);
+
+        for status in [200, 304] {
+            let ok = ConfidenceSignals {
+                http_status: Some(status),
+                ..healthy_signals()
+            };
+            assert_eq!(ConfidenceScorer::new().score(&ok).failure_reason, None, "status {status}");
+        }
+    }
+
+    #[test]
+    fn challenge_scripts_and_meta_refresh_are_detected() {
+        let markers = ChallengeMarkers::default();
+        let by_script = ConfidenceSignals {
+            title: "example.com".into(),
+            ..challenge_signals()
+        };
+        assert_eq!(
+            markers.detect(&by_script).as_deref(),
+            Some("resource:/cdn-cgi/challenge-platform/")
+        );
+
+        let refresh = ConfidenceSignals {
+            meta_refresh: true,
+            body_text_length: 40,
+            ..healthy_signals()
+        };
+        assert_eq!(markers.detect(&refresh).as_deref(), Some("meta_refresh"));
+        let long_refresh = ConfidenceSignals {
+            meta_refresh: true,
+            ..healthy_signals()
+        };
+        assert_eq!(markers.detect(&long_refresh), None);
+        assert_eq!(markers.detect(&healthy_signals()), None);
+    }
+
+    #[test]
+    fn challenge_markers_are_configurable() {
+        let scorer = ConfidenceScorer::new().with_challenge_markers(ChallengeMarkers {
+            titles: vec!["Bot Check".into()],
+            resources: vec![],
+            meta_refresh_max_body_text: 0,
+        });
+        let default_marker = scorer.score(&ConfidenceSignals {
+            script_srcs: vec![],
+            form_actions: vec![],
+            ..challenge_signals()
+        });
+        assert_eq!(default_marker.decision, EngineDecision::StayOnServo);
+
+        let custom = scorer.score(&ConfidenceSignals {
+            title: "BOT CHECK in progress".into(),
+            ..healthy_signals()
+        });
+        assert_eq!(
+            custom.failure_reason,
+            Some(FailureReason::ChallengePage {
+                marker: "title:Bot Check".into()
+            })
+        );
+    }
+
+    fn walk(scorer: &ConfidenceScorer, scores: &[f32]) -> Vec<bool> {
+        let mut previous: Option<EngineDecision> = None;
+        scores
+            .iter()
+            .map(|&overall| {
+                let decision = scorer.decide(overall, &None, previous.as_ref());
+                let escalated = matches!(decision, EngineDecision::EscalateToLadybird(_));
+                previous = Some(decision);
+                escalated
+            })
+            .collect()
+    }
+
+    #[test]
+    fn band_holds_decision_while_score_falls_through_it() {
+        let scorer = ConfidenceScorer::new().with_band(DecisionBand::default());
+        assert_eq!(
+            walk(&scorer, &[0.70, 0.64, 0.601, 0.599, 0.56, 0.549, 0.50]),
+            vec![false, false, false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn band_holds_decision_while_score_rises_through_it() {
+        let scorer = ConfidenceScorer::new().with_band(DecisionBand::default());
+        assert_eq!(
+            walk(&scorer, &[0.50, 0.56, 0.599, 0.601, 0.649, 0.65, 0.62]),
+            vec![true, true, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn band_without_history_falls_back_to_threshold() {
+        let scorer = ConfidenceScorer::new().with_band(DecisionBand::default());
+        assert_eq!(walk(&scorer, &[0.599]), vec![true]);
+        assert_eq!(walk(&scorer, &[0.601]), vec![false]);
+
+        // Without a band the threshold alone decides, whatever came before.
+        assert_eq!(walk(&ConfidenceScorer::new(), &[0.70, 0.599, 0.601]), vec![false, true, false]);
+    }
+
+    #[test]
+    fn failure_reasons_escalate_regardless_of_band() {
+        let scorer = ConfidenceScorer::new().with_band(DecisionBand::default());
+        let report = scorer.score_with_previous(
+            &ConfidenceSignals {
+                js_errors: 10,
+                ..healthy_signals()
+            },
+            Some(&EngineDecision::StayOnServo),
+        );
+        assert!(matches!(
+            report.decision,
+            EngineDecision::EscalateToLadybird(FailureReason::JsCrashLoop { .. })
+        ));
+    }
+
+    #[test]
+    fn explanation_names_the_dominant_factor() {
+        let scorer = ConfidenceScorer::new();
+        let explain = |signals: ConfidenceSignals| scorer.score(&signals).explain();
+
+        let zero_paint = explain(ConfidenceSignals {
+            first_paint_ms: None,
+            ..healthy_signals()
+        });
+        assert!(zero_paint.starts_with("Escalating: paint score 0.00 (no first-paint observed)"), "{zero_paint}");
+        assert!(zero_paint.contains("= paint 0.00 + dom "), "{zero_paint}");
+
+        let spa_shell = explain(ConfidenceSignals {
+            dom_element_count: 3,
+            body_text_length: 10,
+            ..healthy_signals()
+        });
+        assert!(spa_shell.starts_with("Escalating: dom score 0.20 (app shell never hydrated)"), "{spa_shell}");
+
+        let crashing = explain(ConfidenceSignals {
+            js_errors: 5,
+            ..healthy_signals()
+        });
+        assert!(crashing.starts_with("Escalating: js score 0.50 (5 script errors)"), "{crashing}");
+
+        let challenge = explain(challenge_signals());
+        assert!(challenge.contains("anti-bot challenge page (title:just a moment)"), "{challenge}");
+
+        let healthy = explain(ConfidenceSignals {
+            dom_element_count: 10,
+            ..healthy_signals()
+        });
+        assert!(
+            healthy.starts_with("Staying on Servo: no failure detected; dom score 0.50 was the weakest factor"),
+            "{healthy}"
+        );
+
+        let report = scorer.score(&healthy_signals());
+        assert!(report.explain().ends_with(&format!(
+            "Overall {:.2} = paint {:.2} + dom {:.2} + js {:.2} + network {:.2}.",
+            report.overall,
+            report.paint_score * PAINT_WEIGHT,
+            report.dom_score * DOM_WEIGHT,
+            report.js_score * JS_WEIGHT,
+            report.network_score * NETWORK_WEIGHT,
+        )));
+    }
+
+    #[test]
+    fn step_and_logistic_paint_curves_agree_at_the_extremes_only() {
+        let step = PaintCurve::default();
+        let logistic = PaintCurve::Logistic {
+            midpoint_ms: 5000,
+            steepness_ms: 1000,
+            full_element_count: 100,
+        };
+        // (first paint ms, step score, logistic score) with a full element count.
+        let expected = [
+            (500, 1.0, 0.992),
+            (2900, 1.0, 0.924),
+            (3100, 0.6, 0.909),
+            (4000, 0.6, 0.812),
+            (5000, 0.6, 0.65),
+            (9000, 0.3, 0.313),
+            (20000, 0.3, 0.3),
+        ];
+        for (ms, step_score, logistic_score) in expected {
+            assert_eq!(step.score(ms, 100), step_score, "step at {ms}ms");
+            let score = logistic.score(ms, 100);
+            assert!((score - logistic_score).abs() < 0.001, "logistic at {ms}ms: {score}");
+        }
+        // The step curve drops 0.4 across its breakpoint; the logistic one barely moves.
+        assert!(logistic.score(2900, 100) - logistic.score(3100, 100) < 0.02);
+        // Element count scales both the same way while paint is fast.
+        assert_eq!(step.score(500, 50), 0.8);
+        assert!((logistic.score(500, 50) - 0.795).abs() < 0.001);
+
+        let signals = ConfidenceSignals {
+            first_paint_ms: Some(4000),
+            ..healthy_signals()
+        };
+        assert_eq!(ConfidenceScorer::new().score(&signals).paint_score, 0.6, "step is the default");
+        let report = ConfidenceScorer::new().with_paint_curve(logistic).score(&signals);
+        assert!(report.paint_score > 0.7, "{}", report.paint_score);
+    }
 }
@@ -16,19 +16,52 @@ pub struct ConfidenceSignals {
     pub unhandled_promise_rejections: u32,
     pub console_error_count: u32,
     pub js_execution_time_ms: u64,
+    /// `domInteractive` since navigation start: when parsing finished, which
+    /// synchronous scripts push back.
+    #[serde(default)]
+    pub dom_interactive_ms: Option<u64>,
 
     // Network
     pub failed_resource_count: u32,
     pub cors_violations: u32,
     pub pending_requests_at_sample: u32,
+    pub navigation_timings: Option<NavigationTimings>,
+    /// HTTP status of the main document, when the engine reports it.
+    #[serde(default)]
+    pub http_status: Option<u16>,
+    /// MIME type of the main document (`document.contentType`), when the
+    /// engine reports it.
+    #[serde(default)]
+    pub content_type: Option<String>,
 
     // CSS
     pub css_parse_failures: u32,
 
+    // Challenge detection
+    #[serde(default)]
+    pub title: String,
+    /// Whether the document has a `<meta http-equiv="refresh">`.
+    #[serde(default)]
+    pub meta_refresh: bool,
+    /// `src` of the page's external scripts (capped by the probe).
+    #[serde(default)]
+    pub script_srcs: Vec<String>,
+    /// `action` of the page's forms (capped by the probe).
+    #[serde(default)]
+    pub form_actions: Vec<String>,
+
+    // Navigation stability
+    /// URLs the page kept redirecting between while the engine waited for it
+    /// to settle; empty when no loop was seen.
+    #[serde(default)]
+    pub redirect_loop: Vec<String>,
+
     // Timing
     pub sampled_at_ms: u64,
 }
 
+// Spelled out so a new field needs a deliberate default.
+#[allow(clippy::derivable_impls)]
 impl Default for ConfidenceSignals {
     fn default() -> Self {
         Self {
@@ -41,11 +74,374 @@ impl Default for ConfidenceSignals {
             unhandled_promise_rejections: 0,
             console_error_count: 0,
             js_execution_time_ms: 0,
+            dom_interactive_ms: None,
             failed_resource_count: 0,
             cors_violations: 0,
             pending_requests_at_sample: 0,
+            navigation_timings: None,
+            http_status: None,
+            content_type: None,
             css_parse_failures: 0,
+            title: String::new(),
+            meta_refresh: false,
+            script_srcs: Vec::new(),
+            form_actions: Vec::new(),
+            redirect_loop: Vec::new(),
             sampled_at_ms: 0,
         }
     }
 }
+
+impl ConfidenceSignals {
+    /// Start building signals from metrics gathered outside pneuma, e.g. by a
+    /// CDP collector. Unset fields keep their defaults and `sampled_at_ms` is
+    /// stamped with the current time unless set.
+    pub fn builder() -> ConfidenceSignalsBuilder {
+        let sampled_at_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        ConfidenceSignalsBuilder {
+            signals: ConfidenceSignals {
+                sampled_at_ms,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Whether DOM-based scoring applies: the document is HTML, or its type
+    /// is unknown. A JSON or image URL loads as a synthetic document that
+    /// says nothing about how well the engine renders.
+    pub fn is_html(&self) -> bool {
+        let Some(content_type) = &self.content_type else {
+            return true;
+        };
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        essence.eq_ignore_ascii_case("text/html") || essence.eq_ignore_ascii_case("application/xhtml+xml")
+    }
+}
+
+/// Combinations no real page produces, rejected by
+/// [`ConfidenceSignalsBuilder::build`] because the scorer would read them as
+/// a confident (or failed) page when the collector is at fault.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidSignals {
+    #[error("{painted} painted elements but only {elements} in the DOM")]
+    PaintExceedsDom { painted: usize, elements: usize },
+    #[error("DOM depth {depth} exceeds its {elements} elements")]
+    DepthExceedsDom { depth: usize, elements: usize },
+    #[error("{length} characters of body text with no DOM elements")]
+    TextWithoutDom { length: usize },
+    #[error("HTTP status {0} is outside 100-599")]
+    HttpStatus(u16),
+}
+
+/// Typed construction of [`ConfidenceSignals`]; see
+/// [`ConfidenceSignals::builder`].
+#[derive(Debug, Clone)]
+pub struct ConfidenceSignalsBuilder {
+    signals: ConfidenceSignals,
+}
+
+impl ConfidenceSignalsBuilder {
+    pub fn first_paint_ms(mut self, ms: u64) -> Self {
+        self.signals.first_paint_ms = Some(ms);
+        self
+    }
+
+    pub fn paint_element_count(mut self, count: usize) -> Self {
+        self.signals.paint_element_count = count;
+        self
+    }
+
+    pub fn dom_element_count(mut self, count: usize) -> Self {
+        self.signals.dom_element_count = count;
+        self
+    }
+
+    pub fn dom_depth_max(mut self, depth: usize) -> Self {
+        self.signals.dom_depth_max = depth;
+        self
+    }
+
+    pub fn body_text_length(mut self, length: usize) -> Self {
+        self.signals.body_text_length = length;
+        self
+    }
+
+    pub fn js_errors(mut self, count: u32) -> Self {
+        self.signals.js_errors = count;
+        self
+    }
+
+    pub fn unhandled_promise_rejections(mut self, count: u32) -> Self {
+        self.signals.unhandled_promise_rejections = count;
+        self
+    }
+
+    pub fn console_error_count(mut self, count: u32) -> Self {
+        self.signals.console_error_count = count;
+        self
+    }
+
+    pub fn js_execution_time_ms(mut self, ms: u64) -> Self {
+        self.signals.js_execution_time_ms = ms;
+        self
+    }
+
+    pub fn dom_interactive_ms(mut self, ms: u64) -> Self {
+        self.signals.dom_interactive_ms = Some(ms);
+        self
+    }
+
+    pub fn failed_resource_count(mut self, count: u32) -> Self {
+        self.signals.failed_resource_count = count;
+        self
+    }
+
+    pub fn cors_violations(mut self, count: u32) -> Self {
+        self.signals.cors_violations = count;
+        self
+    }
+
+    pub fn pending_requests_at_sample(mut self, count: u32) -> Self {
+        self.signals.pending_requests_at_sample = count;
+        self
+    }
+
+    pub fn navigation_timings(mut self, timings: NavigationTimings) -> Self {
+        self.signals.navigation_timings = Some(timings);
+        self
+    }
+
+    pub fn http_status(mut self, status: u16) -> Self {
+        self.signals.http_status = Some(status);
+        self
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.signals.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn css_parse_failures(mut self, count: u32) -> Self {
+        self.signals.css_parse_failures = count;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.signals.title = title.into();
+        self
+    }
+
+    pub fn meta_refresh(mut self, present: bool) -> Self {
+        self.signals.meta_refresh = present;
+        self
+    }
+
+    pub fn script_srcs(mut self, srcs: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.signals.script_srcs = srcs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn form_actions(mut self, actions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.signals.form_actions = actions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn redirect_loop(mut self, urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.signals.redirect_loop = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn sampled_at_ms(mut self, ms: u64) -> Self {
+        self.signals.sampled_at_ms = ms;
+        self
+    }
+
+    /// The signals, unless they describe a page that cannot exist.
+    pub fn build(self) -> Result<ConfidenceSignals, InvalidSignals> {
+        let signals = self.signals;
+        let elements = signals.dom_element_count;
+        if signals.paint_element_count > elements {
+            return Err(InvalidSignals::PaintExceedsDom {
+                painted: signals.paint_element_count,
+                elements,
+            });
+        }
+        if signals.dom_depth_max > elements {
+            return Err(InvalidSignals::DepthExceedsDom {
+                depth: signals.dom_depth_max,
+                elements,
+            });
+        }
+        if signals.body_text_length > 0 && elements == 0 {
+            return Err(InvalidSignals::TextWithoutDom {
+                length: signals.body_text_length,
+            });
+        }
+        if let Some(status) = signals.http_status.filter(|status| !(100..=599).contains(status)) {
+            return Err(InvalidSignals::HttpStatus(status));
+        }
+        Ok(signals)
+    }
+}
+
+/// Navigation Timing marks in milliseconds relative to navigation start, as
+/// reported under `navigation_timings` in navigate metadata. Marks the page did
+/// not reach (or the engine does not expose) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigationTimings {
+    pub domain_lookup_start: Option<u64>,
+    pub domain_lookup_end: Option<u64>,
+    pub connect_start: Option<u64>,
+    pub connect_end: Option<u64>,
+    pub request_start: Option<u64>,
+    pub response_start: Option<u64>,
+    pub dom_content_loaded_event_end: Option<u64>,
+    pub load_event_end: Option<u64>,
+}
+
+impl NavigationTimings {
+    pub fn dns_ms(&self) -> Option<u64> {
+        span(self.domain_lookup_start, self.domain_lookup_end)
+    }
+
+    pub fn connect_ms(&self) -> Option<u64> {
+        span(self.connect_start, self.connect_end)
+    }
+
+    /// Time to first byte: request sent to first response byte.
+    pub fn ttfb_ms(&self) -> Option<u64> {
+        span(self.request_start, self.response_start)
+    }
+
+    /// DOMContentLoaded completion since navigation start.
+    pub fn dom_content_loaded_ms(&self) -> Option<u64> {
+        self.dom_content_loaded_event_end
+    }
+}
+
+fn span(start: Option<u64>, end: Option<u64>) -> Option<u64> {
+    end?.checked_sub(start?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NavigationTimings;
+
+    #[test]
+    fn derives_durations_from_navigation_timing_marks() {
+        let timings: NavigationTimings = serde_json::from_value(serde_json::json!({
+            "domain_lookup_start": 5,
+            "domain_lookup_end": 25,
+            "connect_start": 25,
+            "connect_end": 70,
+            "request_start": 72,
+            "response_start": 190,
+            "dom_content_loaded_event_end": 640,
+        }))
+        .expect("timings should deserialize");
+
+        assert_eq!(timings.dns_ms(), Some(20));
+        assert_eq!(timings.connect_ms(), Some(45));
+        assert_eq!(timings.ttfb_ms(), Some(118));
+        assert_eq!(timings.dom_content_loaded_ms(), Some(640));
+        assert_eq!(timings.load_event_end, None);
+    }
+
+    #[test]
+    fn missing_or_inverted_marks_yield_no_duration() {
+        let timings = NavigationTimings {
+            request_start: Some(300),
+            response_start: Some(120),
+            connect_end: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(timings.ttfb_ms(), None);
+        assert_eq!(timings.connect_ms(), None);
+        assert_eq!(timings.dns_ms(), None);
+    }
+
+    #[test]
+    fn builder_matches_manual_field_assignment() {
+        use super::ConfidenceSignals;
+        use crate::confidence::ConfidenceScorer;
+
+        let manual = ConfidenceSignals {
+            first_paint_ms: Some(850),
+            paint_element_count: 40,
+            dom_element_count: 180,
+            dom_depth_max: 12,
+            body_text_length: 2400,
+            js_errors: 1,
+            console_error_count: 2,
+            js_execution_time_ms: 300,
+            failed_resource_count: 3,
+            http_status: Some(200),
+            title: "Dashboard".into(),
+            script_srcs: vec!["https://cdn.example/app.js".into()],
+            sampled_at_ms: 1_700_000_000_000,
+            ..Default::default()
+        };
+        let built = ConfidenceSignals::builder()
+            .first_paint_ms(850)
+            .paint_element_count(40)
+            .dom_element_count(180)
+            .dom_depth_max(12)
+            .body_text_length(2400)
+            .js_errors(1)
+            .console_error_count(2)
+            .js_execution_time_ms(300)
+            .failed_resource_count(3)
+            .http_status(200)
+            .title("Dashboard")
+            .script_srcs(["https://cdn.example/app.js"])
+            .sampled_at_ms(1_700_000_000_000)
+            .build()
+            .expect("a plausible page should build");
+
+        assert_eq!(
+            serde_json::to_value(&built).unwrap(),
+            serde_json::to_value(&manual).unwrap()
+        );
+        let scorer = ConfidenceScorer::new();
+        let (from_builder, from_fields) = (scorer.score(&built), scorer.score(&manual));
+        assert_eq!(from_builder.overall, from_fields.overall);
+        assert_eq!(from_builder.decision, from_fields.decision);
+    }
+
+    #[test]
+    fn builder_rejects_impossible_combinations() {
+        use super::{ConfidenceSignals, InvalidSignals};
+
+        let page = || ConfidenceSignals::builder().dom_element_count(10);
+        assert_eq!(
+            page().paint_element_count(11).build().unwrap_err(),
+            InvalidSignals::PaintExceedsDom {
+                painted: 11,
+                elements: 10
+            }
+        );
+        assert_eq!(
+            page().dom_depth_max(30).build().unwrap_err(),
+            InvalidSignals::DepthExceedsDom {
+                depth: 30,
+                elements: 10
+            }
+        );
+        assert_eq!(
+            ConfidenceSignals::builder().body_text_length(5).build().unwrap_err(),
+            InvalidSignals::TextWithoutDom { length: 5 }
+        );
+        assert_eq!(
+            page().http_status(0).build().unwrap_err(),
+            InvalidSignals::HttpStatus(0)
+        );
+        assert!(
+            ConfidenceSignals::builder().build().is_ok(),
+            "an empty page is a real outcome"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use crate::confidence::{
+    ConfidenceScorer, DecisionBand, HttpErrorAction, PaintCurve, DEFAULT_DOM_INTERACTIVE_THRESHOLD_MS,
+};
+use crate::diagnostics::DiagnosticsBundle;
+use crate::endpoint_pool::EndpointPool;
+use crate::engine_factory::DefaultEscalationEngineFactory;
+use crate::migration::FileStateStore;
+use crate::service::{layer_engine_timeouts, EmptyStateHandoff, ServiceOptions};
+
+/// `[broker]` section of the runtime config file. Every field is optional;
+/// unset ones keep what [`ServiceOptions::from_env`] chose.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrokerConfig {
+    /// Overrides `PNEUMA_MAX_ESCALATIONS`.
+    pub max_escalations: Option<u32>,
+    /// Overrides `PNEUMA_STATE_DIR`.
+    pub state_dir: Option<PathBuf>,
+    /// Overrides `PNEUMA_DIAG_DIR`.
+    pub diag_dir: Option<PathBuf>,
+    /// Init script directory, used when `--init-scripts` is not given.
+    pub init_scripts: Option<PathBuf>,
+    /// Overrides `PNEUMA_STAMP_MIGRATED`.
+    pub stamp_migrated: Option<bool>,
+    /// Overrides `PNEUMA_MIGRATED_KEY`.
+    pub migrated_key: Option<String>,
+    /// Overrides `SERVO_SECONDARY_WEBDRIVER_URLS`.
+    pub secondary_webdriver_urls: Option<Vec<String>>,
+    /// Overrides `PNEUMA_COALESCE_NAVIGATES`.
+    pub coalesce_navigates: Option<bool>,
+    /// Overrides `PNEUMA_EMPTY_STATE_HANDOFF`: `proceed` or `abort`.
+    pub empty_state_handoff: Option<EmptyStateHandoff>,
+    /// Overrides `PNEUMA_STRICT_ESCALATION`.
+    pub strict_escalation: Option<bool>,
+    /// Overrides `PNEUMA_ENGINE_NAVIGATE_TIMEOUT_MS`.
+    pub navigate_timeout_ms: Option<u64>,
+    /// Overrides `PNEUMA_ENGINE_TIMEOUT_MS`.
+    pub engine_timeout_ms: Option<u64>,
+}
+
+impl BrokerConfig {
+    /// Layer the configured values over `options`.
+    pub fn apply(&self, options: &mut ServiceOptions) {
+        if let Some(max_escalations) = self.max_escalations {
+            options.max_escalations = max_escalations;
+        }
+        if let Some(dir) = &self.state_dir {
+            options.store = Some(Box::new(FileStateStore::new(dir)));
+        }
+        if let Some(dir) = &self.diag_dir {
+            options.diagnostics = Some(DiagnosticsBundle::new(dir));
+        }
+        if let Some(stamp_migrated) = self.stamp_migrated {
+            options.stamp_migrated = stamp_migrated;
+        }
+        if let Some(key) = &self.migrated_key {
+            options.migrated_key = key.clone();
+        }
+        if let Some(coalesce_navigates) = self.coalesce_navigates {
+            options.coalesce_navigates = coalesce_navigates;
+        }
+        if let Some(empty_state_handoff) = self.empty_state_handoff {
+            options.empty_state_handoff = empty_state_handoff;
+        }
+        if let Some(strict_escalation) = self.strict_escalation {
+            options.strict_escalation = strict_escalation;
+        }
+        options.engine_timeouts = layer_engine_timeouts(
+            options.engine_timeouts,
+            self.navigate_timeout_ms.map(Duration::from_millis),
+            self.engine_timeout_ms.map(Duration::from_millis),
+        );
+    }
+
+    /// Escalation factory over the configured secondary endpoints, or the
+    /// environment's when none are configured.
+    pub fn escalation_factory(&self) -> DefaultEscalationEngineFactory {
+        match &self.secondary_webdriver_urls {
+            Some(urls) => DefaultEscalationEngineFactory::with_pool(EndpointPool::new(urls)),
+            None => DefaultEscalationEngineFactory::from_env(),
+        }
+    }
+}
+
+/// `[scorer]` section of the runtime config file: the escalation band and
+/// the paint score curve.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScorerConfig {
+    pub escalate_below: Option<f32>,
+    pub stay_at: Option<f32>,
+    /// `escalate` (default) or `report` for 4xx/5xx main documents.
+    pub http_errors: Option<HttpErrorAction>,
+    /// `domInteractive` in ms past which the JS score drops (default 10000).
+    pub dom_interactive_threshold_ms: Option<u64>,
+    /// Lower the network score for a slow time to first byte (default off).
+    pub ttfb_penalty: Option<bool>,
+    pub paint: PaintConfig,
+}
+
+/// `[scorer.paint]`: which [`PaintCurve`] to use and its parameters. Unset
+/// ones keep the defaults; parameters of the other curve are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaintConfig {
+    pub curve: Option<PaintCurveKind>,
+    /// Step curve: first paint slower than this scores 0.6 (default 3000).
+    pub slow_ms: Option<u64>,
+    /// Step curve: first paint slower than this scores 0.3 (default 8000).
+    pub very_slow_ms: Option<u64>,
+    /// Logistic curve: first paint time at which the score is halfway down
+    /// (default 5000).
+    pub midpoint_ms: Option<u64>,
+    /// Logistic curve: how gradually the score falls around the midpoint
+    /// (default 1000).
+    pub steepness_ms: Option<u64>,
+    /// Painted elements for a full score (default 100).
+    pub full_element_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaintCurveKind {
+    Step,
+    Logistic,
+}
+
+impl ScorerConfig {
+    /// A scorer with the configured band and paint curve.
+    pub fn scorer(&self) -> Result<ConfidenceScorer> {
+        Ok(ConfidenceScorer::new()
+            .with_band(self.decision_band()?)
+            .with_paint_curve(self.paint_curve()?)
+            .with_http_errors(self.http_errors.unwrap_or_default())
+            .with_dom_interactive_threshold_ms(self.dom_interactive_threshold_ms()?)
+            .with_ttfb_penalty(self.ttfb_penalty.unwrap_or(false)))
+    }
+
+    /// The configured `domInteractive` threshold; fails when it is zero.
+    pub fn dom_interactive_threshold_ms(&self) -> Result<u64> {
+        let threshold_ms = self.dom_interactive_threshold_ms.unwrap_or(DEFAULT_DOM_INTERACTIVE_THRESHOLD_MS);
+        if threshold_ms == 0 {
+            bail!("scorer.dom_interactive_threshold_ms must be positive");
+        }
+        Ok(threshold_ms)
+    }
+
+    /// The configured paint curve; fails on parameters that make no sense.
+    pub fn paint_curve(&self) -> Result<PaintCurve> {
+        let paint = &self.paint;
+        let full_element_count = paint.full_element_count.unwrap_or(100);
+        if full_element_count == 0 {
+            bail!("scorer.paint.full_element_count must be positive");
+        }
+        match paint.curve.unwrap_or(PaintCurveKind::Step) {
+            PaintCurveKind::Step => {
+                let slow_ms = paint.slow_ms.unwrap_or(3000);
+                let very_slow_ms = paint.very_slow_ms.unwrap_or(8000);
+                if slow_ms > very_slow_ms {
+                    bail!(
+                        "scorer.paint.slow_ms ({slow_ms}) must not exceed scorer.paint.very_slow_ms ({very_slow_ms})"
+                    );
+                }
+                Ok(PaintCurve::Step {
+                    slow_ms,
+                    very_slow_ms,
+                    full_element_count,
+                })
+            }
+            PaintCurveKind::Logistic => {
+                let steepness_ms = paint.steepness_ms.unwrap_or(1000);
+                if steepness_ms == 0 {
+                    bail!("scorer.paint.steepness_ms must be positive");
+                }
+                Ok(PaintCurve::Logistic {
+                    midpoint_ms: paint.midpoint_ms.unwrap_or(5000),
+                    steepness_ms,
+                    full_element_count,
+                })
+            }
+        }
+    }
+
+    /// The default band with the configured edges. Fails when an edge is
+    /// outside `0..=1` or the band is inverted.
+    pub fn decision_band(&self) -> Result<DecisionBand> {
+        let defaults = DecisionBand::default();
+        let band = DecisionBand {
+            escalate_below: self.escalate_below.unwrap_or(defaults.escalate_below),
+            stay_at: self.stay_at.unwrap_or(defaults.stay_at),
+        };
+        for (name, edge) in [("escalate_below", band.escalate_below), ("stay_at", band.stay_at)] {
+            if !(0.0..=1.0).contains(&edge) {
+                bail!("scorer.{name} must be between 0 and 1, got {edge}");
+            }
+        }
+        if band.escalate_below > band.stay_at {
+            bail!(
+                "scorer.escalate_below ({}) must not exceed scorer.stay_at ({})",
+                band.escalate_below,
+                band.stay_at
+            );
+        }
+        Ok(band)
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use pneuma_engines::HeadlessEngine;
+use serde_json::Value;
+
+use crate::confidence::ConfidenceReport;
+
+/// Bound on each engine call made while collecting a bundle; the primary may
+/// be the reason the handoff failed.
+const CAPTURE_STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+const PAGE_SOURCE_SCRIPT: &str = "document.documentElement ? document.documentElement.outerHTML : ''";
+
+/// What went wrong with a handoff, for the bundle's `error.txt`.
+pub struct EscalationFailure<'a> {
+    pub session_id: &'a str,
+    pub page_id: u32,
+    pub url: &'a str,
+    pub report: &'a ConfidenceReport,
+    /// Full error chain (`{:?}` of an `anyhow::Error`) or a timeout note.
+    pub error: String,
+}
+
+/// Writes a debugging bundle when an escalation handoff fails or times out:
+/// `page.html` (primary's page source), `screenshot.png`, `report.json` and
+/// `error.txt`, in a fresh directory under the configured root.
+///
+/// Enabled by `PNEUMA_DIAG_DIR`. Collection is best-effort: an artifact that
+/// cannot be captured is logged and left out.
+#[derive(Debug, Clone)]
+pub struct DiagnosticsBundle {
+    dir: PathBuf,
+}
+
+impl DiagnosticsBundle {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Bundles rooted at `PNEUMA_DIAG_DIR`, or `None` when unset.
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("PNEUMA_DIAG_DIR").ok()?;
+        let dir = dir.trim();
+        (!dir.is_empty()).then(|| Self::new(dir))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Collect and write a bundle for `failure`, returning its directory.
+    /// Fails only when the bundle directory itself cannot be created.
+    pub async fn capture(&self, primary: &dyn HeadlessEngine, failure: &EscalationFailure<'_>) -> Result<PathBuf> {
+        let captured_at_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |now| now.as_millis());
+        let bundle = self.dir.join(format!(
+            "escalation-{}-page{}-{captured_at_ms}",
+            failure.session_id, failure.page_id
+        ));
+        tokio::fs::create_dir_all(&bundle)
+            .await
+            .with_context(|| format!("failed to create diagnostics directory {}", bundle.display()))?;
+
+        let error = format!(
+            "session: {}\npage: {}\nurl: {}\n\n{}\n",
+            failure.session_id, failure.page_id, failure.url, failure.error
+        );
+        write_artifact(&bundle, "error.txt", error.into_bytes()).await;
+
+        match serde_json::to_vec_pretty(failure.report) {
+            Ok(report) => write_artifact(&bundle, "report.json", report).await,
+            Err(error) => skip_artifact("report.json", &error),
+        }
+
+        match tokio::time::timeout(CAPTURE_STEP_TIMEOUT, primary.evaluate(PAGE_SOURCE_SCRIPT)).await {
+            Ok(Ok(raw)) => {
+                // Evaluate results are JSON; unwrap the string rather than write it quoted.
+                let source = match serde_json::from_str(&raw) {
+                    Ok(Value::String(source)) => source,
+                    _ => raw,
+                };
+                write_artifact(&bundle, "page.html", source.into_bytes()).await;
+            }
+            Ok(Err(error)) => skip_artifact("page.html", &error),
+            Err(_) => skip_artifact("page.html", &"timed out"),
+        }
+
+        match tokio::time::timeout(CAPTURE_STEP_TIMEOUT, primary.screenshot()).await {
+            Ok(Ok(png)) => write_artifact(&bundle, "screenshot.png", png).await,
+            Ok(Err(error)) => skip_artifact("screenshot.png", &error),
+            Err(_) => skip_artifact("screenshot.png", &"timed out"),
+        }
+
+        Ok(bundle)
+    }
+}
+
+async fn write_artifact(bundle: &Path, name: &str, contents: Vec<u8>) {
+    let path = bundle.join(name);
+    if let Err(error) = tokio::fs::write(&path, contents).await {
+        tracing::warn!(
+            target: "pneuma_broker",
+            path = %path.display(),
+            error = %error,
+            "failed to write diagnostics artifact"
+        );
+    }
+}
+
+fn skip_artifact(name: &str, error: &dyn std::fmt::Display) {
+    tracing::warn!(
+        target: "pneuma_broker",
+        artifact = name,
+        error = %error,
+        "failed to capture diagnostics artifact; leaving it out"
+    );
+}
@@ -0,0 +1,257 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use pneuma_engines::{EngineKind, HeadlessEngine, MigrationCookie, MigrationEnvelope};
+
+/// Pre-started secondary WebDriver endpoints handed out round-robin.
+///
+/// An endpoint stays leased until the engine attached to it is dropped, so two
+/// concurrent escalations never share one secondary.
+#[derive(Debug, Clone)]
+pub struct EndpointPool {
+    inner: Arc<Mutex<PoolState>>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    endpoints: Vec<String>,
+    in_use: Vec<bool>,
+    next: usize,
+}
+
+impl EndpointPool {
+    /// Build a pool from trimmed, non-empty endpoint URLs. Returns `None` when
+    /// none are left.
+    pub fn new<I, S>(endpoints: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let endpoints: Vec<String> = endpoints
+            .into_iter()
+            .map(|endpoint| endpoint.as_ref().trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+        if endpoints.is_empty() {
+            return None;
+        }
+        let in_use = vec![false; endpoints.len()];
+        Some(Self {
+            inner: Arc::new(Mutex::new(PoolState {
+                endpoints,
+                in_use,
+                next: 0,
+            })),
+        })
+    }
+
+    /// Pool from the comma-separated `SERVO_SECONDARY_WEBDRIVER_URLS`, if set.
+    pub fn from_env() -> Option<Self> {
+        let urls = std::env::var("SERVO_SECONDARY_WEBDRIVER_URLS").ok()?;
+        Self::new(urls.split(','))
+    }
+
+    /// Lease the next free endpoint after the last one handed out, or `None`
+    /// when every endpoint is in use.
+    pub fn acquire(&self) -> Option<EndpointLease> {
+        let mut state = self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let len = state.endpoints.len();
+        let index = (0..len)
+            .map(|offset| (state.next + offset) % len)
+            .find(|&index| !state.in_use[index])?;
+        state.in_use[index] = true;
+        state.next = (index + 1) % len;
+        Some(EndpointLease {
+            pool: self.inner.clone(),
+            index,
+            endpoint: state.endpoints[index].clone(),
+        })
+    }
+}
+
+/// A leased endpoint; returned to the pool on drop.
+#[derive(Debug)]
+pub struct EndpointLease {
+    pool: Arc<Mutex<PoolState>>,
+    index: usize,
+    endpoint: String,
+}
+
+impl EndpointLease {
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+impl Drop for EndpointLease {
+    fn drop(&mut self) {
+        let mut state = self.pool.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.in_use[self.index] = false;
+    }
+}
+
+/// An engine attached to a pooled endpoint, holding its lease for as long as
+/// the engine lives.
+pub struct PooledEngine {
+    engine: Box<dyn HeadlessEngine>,
+    _lease: EndpointLease,
+}
+
+impl PooledEngine {
+    pub fn new(engine: Box<dyn HeadlessEngine>, lease: EndpointLease) -> Self {
+        Self { engine, _lease: lease }
+    }
+}
+
+#[async_trait]
+impl HeadlessEngine for PooledEngine {
+    fn kind(&self) -> EngineKind {
+        self.engine.kind()
+    }
+    fn name(&self) -> &'static str {
+        self.engine.name()
+    }
+    fn instance_id(&self) -> &str {
+        self.engine.instance_id()
+    }
+    fn cancel(&self) {
+        self.engine.cancel()
+    }
+    async fn navigate(&self, url: &str, opts_json: &str) -> Result<String> {
+        self.engine.navigate(url, opts_json).await
+    }
+    async fn evaluate(&self, script: &str) -> Result<String> {
+        self.engine.evaluate(script).await
+    }
+    async fn screenshot(&self) -> Result<Vec<u8>> {
+        self.engine.screenshot().await
+    }
+    async fn screenshot_element(&self, selector: &str) -> Result<Vec<u8>> {
+        self.engine.screenshot_element(selector).await
+    }
+    async fn close(&self) -> Result<()> {
+        self.engine.close().await
+    }
+    async fn add_init_script(&self, script: &str) -> Result<()> {
+        self.engine.add_init_script(script).await
+    }
+    async fn window_handles(&self) -> Result<Vec<String>> {
+        self.engine.window_handles().await
+    }
+    async fn open_window(&self) -> Result<String> {
+        self.engine.open_window().await
+    }
+    async fn switch_to_window(&self, handle: &str) -> Result<()> {
+        self.engine.switch_to_window(handle).await
+    }
+    async fn close_window(&self, handle: &str) -> Result<()> {
+        self.engine.close_window(handle).await
+    }
+    async fn current_url(&self) -> Result<Option<String>> {
+        self.engine.current_url().await
+    }
+    async fn get_cookies(&self) -> Result<Vec<MigrationCookie>> {
+        self.engine.get_cookies().await
+    }
+    async fn extract_state(&self) -> Result<MigrationEnvelope> {
+        self.engine.extract_state().await
+    }
+    async fn import_state(&self, state: MigrationEnvelope) -> Result<()> {
+        self.engine.import_state(state).await
+    }
+    async fn reset_state(&self) -> Result<()> {
+        self.engine.reset_state().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EndpointPool, PooledEngine};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use pneuma_engines::{EngineKind, HeadlessEngine, MigrationEnvelope};
+
+    const ENDPOINTS: [&str; 3] = ["http://a:4444", "http://b:4444", "http://c:4444"];
+
+    #[test]
+    fn sequential_acquires_round_robin_across_endpoints() {
+        let pool = EndpointPool::new(ENDPOINTS).expect("pool should be non-empty");
+        let handed_out: Vec<String> = (0..7)
+            .map(|_| pool.acquire().expect("an endpoint should be free").endpoint().to_string())
+            .collect();
+        assert_eq!(
+            handed_out,
+            vec![
+                "http://a:4444",
+                "http://b:4444",
+                "http://c:4444",
+                "http://a:4444",
+                "http://b:4444",
+                "http://c:4444",
+                "http://a:4444",
+            ]
+        );
+    }
+
+    #[test]
+    fn leased_endpoints_are_not_assigned_twice() {
+        let pool = EndpointPool::new(ENDPOINTS).unwrap();
+        let first = pool.acquire().unwrap();
+        let second = pool.acquire().unwrap();
+        let third = pool.acquire().unwrap();
+        assert!(pool.acquire().is_none(), "all endpoints are leased");
+
+        drop(second);
+        let reused = pool.acquire().expect("released endpoint should be free");
+        assert_eq!(reused.endpoint(), "http://b:4444");
+        assert!(pool.acquire().is_none());
+        drop((first, third, reused));
+    }
+
+    #[test]
+    fn blank_entries_are_ignored() {
+        assert!(EndpointPool::new([" ", ""]).is_none());
+        let pool = EndpointPool::new([" http://a:4444 ", ""]).unwrap();
+        assert_eq!(pool.acquire().unwrap().endpoint(), "http://a:4444");
+    }
+
+    #[tokio::test]
+    async fn pooled_engine_forwards_the_current_url() {
+        struct AtUrl;
+        #[async_trait]
+        impl HeadlessEngine for AtUrl {
+            fn kind(&self) -> EngineKind {
+                EngineKind::Servo
+            }
+            fn name(&self) -> &'static str {
+                "at-url"
+            }
+            async fn navigate(&self, _: &str, _: &str) -> Result<String> {
+                Ok("{}".into())
+            }
+            async fn evaluate(&self, _: &str) -> Result<String> {
+                Ok("null".into())
+            }
+            async fn screenshot(&self) -> Result<Vec<u8>> {
+                Ok(Vec::new())
+            }
+            async fn close(&self) -> Result<()> {
+                Ok(())
+            }
+            async fn current_url(&self) -> Result<Option<String>> {
+                Ok(Some("https://example.com/".into()))
+            }
+            async fn extract_state(&self) -> Result<MigrationEnvelope> {
+                anyhow::bail!("unused")
+            }
+            async fn import_state(&self, _: MigrationEnvelope) -> Result<()> {
+                anyhow::bail!("unused")
+            }
+        }
+
+        let pool = EndpointPool::new(ENDPOINTS).unwrap();
+        let engine = PooledEngine::new(Box::new(AtUrl), pool.acquire().unwrap());
+        assert_eq!(engine.current_url().await.unwrap().as_deref(), Some("https://example.com/"));
+    }
+}
@@ -1,7 +1,11 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use pneuma_engines::{EngineKind, HeadlessEngine};
 
+use crate::endpoint_pool::{EndpointPool, PooledEngine};
+
 /// Abstraction over secondary engine creation, primarily for testability.
 ///
 /// The `target` argument reflects the decision the confidence scorer made
@@ -11,14 +15,47 @@ use pneuma_engines::{EngineKind, HeadlessEngine};
 #[async_trait]
 pub trait EscalationEngineFactory: Send + Sync {
     async fn create_for_escalation(&self, target: EngineKind) -> Result<Box<dyn HeadlessEngine>>;
+
+    /// Create a fresh primary to replace a wedged one (`BrokerRequest::ResetEngine`).
+    /// Defaults to the escalation path with a Servo target.
+    async fn create_primary(&self) -> Result<Box<dyn HeadlessEngine>> {
+        self.create_for_escalation(EngineKind::Servo).await
+    }
 }
 
 /// Default factory used in production.
 ///
 /// Resolution order for the secondary Servo instance:
-/// 1. `SERVO_SECONDARY_WEBDRIVER_URL` — attach to existing process.
-/// 2. Spawn a fresh local Servo process.
-pub struct DefaultEscalationEngineFactory;
+/// 1. `SERVO_SECONDARY_WEBDRIVER_URLS` — attach to the next free endpoint of
+///    the comma-separated pool (round-robin; see [`EndpointPool`]).
+/// 2. `SERVO_SECONDARY_WEBDRIVER_URL` — attach to existing process. Only used
+///    when no pool is configured.
+/// 3. Spawn a fresh local Servo process, also when every pooled endpoint is busy.
+///
+/// Spawned processes, secondary or replacement primary, use the prefs file set
+/// with [`with_servo_prefs`](Self::with_servo_prefs), else `PNEUMA_SERVO_PREFS`.
+#[derive(Default)]
+pub struct DefaultEscalationEngineFactory {
+    pool: Option<EndpointPool>,
+    servo_prefs: Option<PathBuf>,
+}
+
+impl DefaultEscalationEngineFactory {
+    pub fn from_env() -> Self {
+        Self::with_pool(EndpointPool::from_env())
+    }
+
+    pub fn with_pool(pool: Option<EndpointPool>) -> Self {
+        Self { pool, servo_prefs: None }
+    }
+
+    /// Spawn Servo with `prefs` (e.g. the config file's `engine.servo_prefs`)
+    /// so escalations and resets match the startup engine.
+    pub fn with_servo_prefs(mut self, prefs: Option<PathBuf>) -> Self {
+        self.servo_prefs = prefs;
+        self
+    }
+}
 
 #[async_trait]
 impl EscalationEngineFactory for DefaultEscalationEngineFactory {
@@ -41,7 +78,27 @@ impl EscalationEngineFactory for DefaultEscalationEngineFactory {
             }
         }
 
-        if let Ok(url) = std::env::var("SERVO_SECONDARY_WEBDRIVER_URL") {
+        if let Some(pool) = &self.pool {
+            match pool.acquire() {
+                Some(lease) => {
+                    tracing::info!(
+                        target: "pneuma_broker",
+                        base_url = %lease.endpoint(),
+                        "escalation factory: attaching to pooled secondary endpoint"
+                    );
+                    let engine =
+                        pneuma_engines::servo::ServoEngine::launch_with_endpoint(lease.endpoint().to_string())
+                            .await?;
+                    return Ok(Box::new(PooledEngine::new(Box::new(engine), lease)));
+                }
+                None => {
+                    tracing::warn!(
+                        target: "pneuma_broker",
+                        "escalation factory: all pooled secondary endpoints are in use"
+                    );
+                }
+            }
+        } else if let Ok(url) = std::env::var("SERVO_SECONDARY_WEBDRIVER_URL") {
             let trimmed = url.trim().to_string();
             if !trimmed.is_empty() {
                 tracing::info!(
@@ -56,9 +113,22 @@ impl EscalationEngineFactory for DefaultEscalationEngineFactory {
 
         tracing::info!(
             target: "pneuma_broker",
-            "escalation factory: no endpoint env var set; spawning local Servo process for secondary"
+            "escalation factory: no secondary endpoint available; spawning local Servo process for secondary"
         );
-        let engine = pneuma_engines::servo::ServoEngine::launch_spawned().await?;
+        let engine = match &self.servo_prefs {
+            Some(prefs) => pneuma_engines::servo::ServoEngine::launch_spawned_with_prefs(Some(prefs.clone())).await?,
+            None => pneuma_engines::servo::ServoEngine::launch_spawned().await?,
+        };
+        Ok(Box::new(engine))
+    }
+
+    /// Same resolution as startup: `SERVO_WEBDRIVER_URL`, else spawn locally.
+    async fn create_primary(&self) -> Result<Box<dyn HeadlessEngine>> {
+        tracing::info!(target: "pneuma_broker", "engine factory: creating replacement primary Servo instance");
+        let engine = match &self.servo_prefs {
+            Some(prefs) => pneuma_engines::servo::ServoEngine::launch_with_prefs(Some(prefs.clone())).await?,
+            None => pneuma_engines::servo::ServoEngine::launch().await?,
+        };
         Ok(Box::new(engine))
     }
 }
@@ -0,0 +1,52 @@
+use serde::Serialize;
+
+use crate::confidence::FailureReason;
+
+/// Typed notices of engine changes, sent on
+/// [`ServiceOptions::events`](crate::service::ServiceOptions::events) so
+/// consumers can react without scraping logs. Sends never block the broker; events sent after the
+/// receiver is dropped are discarded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BrokerEvent {
+    /// A navigate scored for escalation and its handoff started.
+    EscalationAttempted {
+        page_id: u32,
+        correlation_id: u64,
+        url: String,
+        reason: FailureReason,
+        primary_instance: String,
+    },
+    /// The secondary took over and now serves every page.
+    EscalationSucceeded {
+        page_id: u32,
+        correlation_id: u64,
+        secondary_instance: String,
+        duration_ms: u64,
+    },
+    /// The handoff failed, timed out or was abandoned; the navigate got the
+    /// primary's result.
+    EscalationFailed {
+        page_id: u32,
+        correlation_id: u64,
+        error: String,
+    },
+    /// The secondary used up its failure budget on `operation` and the
+    /// standby primary is active again.
+    RolledBack {
+        page_id: u32,
+        operation: String,
+        failed_instance: String,
+        restored_instance: String,
+    },
+    /// A `Rollback` request made the standby primary active again.
+    ManualRollback {
+        failed_instance: String,
+        restored_instance: String,
+    },
+    /// `ResetEngine` replaced the active engine with a fresh primary.
+    EngineReset {
+        previous_instance: String,
+        new_instance: String,
+    },
+}
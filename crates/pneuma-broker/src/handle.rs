@@ -1,13 +1,39 @@
-use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
 use tokio::sync::{mpsc, oneshot};
 
+use crate::migration::{MigratableSessionState, MigrationCookie, MigrationEnvelope};
+use crate::policy::NavigationBlocked;
+use crate::result_store::{ResultChunk, StoredResult};
+use pneuma_engines::EngineError;
+
 #[derive(Debug)]
 pub enum BrokerRequest {
     CreatePage {
         reply: oneshot::Sender<Result<u32>>,
     },
+    /// Create a page, navigate it to the envelope's `current_url` to establish
+    /// the origin, then import the envelope's cookies and localStorage.
+    CreatePageWithState {
+        envelope: MigrationEnvelope,
+        reply: oneshot::Sender<Result<u32>>,
+    },
     Navigate {
         page_id: u32,
+        url: String,
+        opts_json: String,
+        /// Tags every log line about this navigate, across broker and engine.
+        correlation_id: u64,
+        reply: oneshot::Sender<Result<String>>,
+    },
+    /// Navigate one engine of every kind to `url` at once, apart from any
+    /// page; replies with a JSON comparison of their results and scores. The
+    /// active engine serves its own kind from a scratch window; other kinds
+    /// get an engine that is closed after the reply.
+    NavigateCompare {
         url: String,
         opts_json: String,
         reply: oneshot::Sender<Result<String>>,
@@ -17,71 +43,596 @@ pub enum BrokerRequest {
         script: String,
         reply: oneshot::Sender<Result<String>>,
     },
+    /// Like `Evaluate`, but keeps the result in the broker and replies with a
+    /// handle for reading it back in chunks.
+    EvaluateStored {
+        page_id: u32,
+        script: String,
+        reply: oneshot::Sender<Result<StoredResult>>,
+    },
+    ReadChunk {
+        handle: u32,
+        offset: usize,
+        len: usize,
+        reply: oneshot::Sender<Result<ResultChunk>>,
+    },
+    /// Release a stored result; replies false for an unknown handle.
+    FreeResult {
+        handle: u32,
+        reply: oneshot::Sender<Result<bool>>,
+    },
+    /// Run `script` on every open page; replies with one result per page id.
+    /// Pages share the engine session's window focus, so they are evaluated
+    /// one after another: N pages cost N evaluate round-trips, not one.
+    EvaluateAll {
+        script: String,
+        reply: oneshot::Sender<Vec<(u32, Result<String>)>>,
+    },
+    /// Register a script to run on every new document, on the active engine
+    /// and on any engine that replaces it.
+    AddInitScript {
+        script: String,
+        reply: oneshot::Sender<Result<()>>,
+    },
     Screenshot {
         page_id: u32,
         reply: oneshot::Sender<Result<Vec<u8>>>,
     },
+    /// PNG of the first element matching the CSS `selector` on the page.
+    ScreenshotElement {
+        page_id: u32,
+        selector: String,
+        reply: oneshot::Sender<Result<Vec<u8>>>,
+    },
+    /// Cookies visible to the page's document, without a full state capture.
+    GetCookies {
+        page_id: u32,
+        reply: oneshot::Sender<Result<Vec<MigrationCookie>>>,
+    },
+    /// Close the page's window and forget the page.
+    ClosePage {
+        page_id: u32,
+        reply: oneshot::Sender<Result<()>>,
+    },
     CloseBrowser {
+        reply: oneshot::Sender<Result<ShutdownReport>>,
+    },
+    /// Snapshot of the session record (active engine, last URL).
+    SessionState {
+        reply: oneshot::Sender<Result<MigratableSessionState>>,
+    },
+    /// Make the standby primary active again and close the secondary,
+    /// without waiting for the failure budget. Replies whether a rollback
+    /// happened: `false` when already on the primary.
+    Rollback {
+        reply: oneshot::Sender<Result<bool>>,
+    },
+    /// Close the active engine and replace it with a fresh primary.
+    ResetEngine {
         reply: oneshot::Sender<Result<()>>,
     },
     Shutdown {
-        reply: oneshot::Sender<Result<()>>,
+        reply: oneshot::Sender<Result<ShutdownReport>>,
     },
 }
 
+/// Outcome of closing the broker's engines on `CloseBrowser` / `Shutdown`.
+///
+/// Close failures are recorded here rather than failing the request, so callers
+/// can tell whether cleanup fully succeeded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShutdownReport {
+    pub active_closed: bool,
+    /// `None` when there was no standby primary to close.
+    pub standby_closed: Option<bool>,
+    pub errors: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Whether every engine that needed closing closed successfully.
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The broker service is gone: a request could not be queued, or its reply
+/// will never come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("broker {channel} channel closed")]
+pub struct ChannelClosed {
+    /// `"request"` or `"reply"`.
+    pub channel: &'static str,
+}
+
+/// Why [`BrokerHandle::navigate`] failed, so callers can tell failures worth
+/// retrying from fatal ones without matching on messages.
+#[derive(Debug, thiserror::Error)]
+pub enum NavigateError {
+    /// The broker service is no longer running.
+    #[error("broker channel closed")]
+    ChannelClosed,
+    /// The engine failed the navigate.
+    #[error(transparent)]
+    Engine(EngineError),
+    /// The engine did not finish the navigate in time.
+    #[error("{0}")]
+    Timeout(String),
+    /// The navigate policy refused the URL; no engine was touched.
+    #[error(transparent)]
+    PolicyBlocked(NavigationBlocked),
+    /// Anything else, e.g. malformed navigate options.
+    #[error("{0:#}")]
+    Other(anyhow::Error),
+}
+
+impl NavigateError {
+    /// Stable snake_case name of the variant, e.g. for JS error objects.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ChannelClosed => "channel_closed",
+            Self::Engine(_) => "engine",
+            Self::Timeout(_) => "timeout",
+            Self::PolicyBlocked(_) => "policy_blocked",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// Whether the same navigate may succeed if repeated: timeouts and
+    /// transient engine errors (see [`EngineError::is_transient`]).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Engine(error) => error.is_transient(),
+            Self::Timeout(_) => true,
+            Self::ChannelClosed | Self::PolicyBlocked(_) | Self::Other(_) => false,
+        }
+    }
+}
+
+/// Classify a broker error by the typed error it carries. An [`EngineError`]
+/// is only found at the top of the error or under added context.
+impl From<anyhow::Error> for NavigateError {
+    fn from(error: anyhow::Error) -> Self {
+        if error.downcast_ref::<ChannelClosed>().is_some() {
+            return Self::ChannelClosed;
+        }
+        let error = match error.downcast::<NavigationBlocked>() {
+            Ok(blocked) => return Self::PolicyBlocked(blocked),
+            Err(error) => error,
+        };
+        match error.downcast::<EngineError>() {
+            Ok(EngineError::Timeout(message)) => Self::Timeout(message),
+            Ok(error) => Self::Engine(error),
+            Err(error) => Self::Other(error),
+        }
+    }
+}
+
+/// Reply to a request queued without waiting, e.g. by
+/// [`BrokerHandle::navigate_pending`]. Poll it with [`try_take`](Self::try_take)
+/// or block on it with [`wait`](Self::wait).
+#[derive(Debug)]
+pub struct PendingReply<T> {
+    rx: oneshot::Receiver<Result<T>>,
+}
+
+impl<T> PendingReply<T> {
+    /// The reply if it has arrived, without blocking.
+    pub fn try_take(&mut self) -> Option<Result<T>> {
+        match self.rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            Err(oneshot::error::TryRecvError::Closed) => Some(Err(ChannelClosed { channel: "reply" }.into())),
+        }
+    }
+
+    /// Block the calling thread until the reply arrives.
+    pub fn wait(self) -> Result<T> {
+        self.rx.blocking_recv().map_err(|_| ChannelClosed { channel: "reply" })?
+    }
+}
+
+/// Receiving end of the broker request channel, bounded or not.
+#[derive(Debug)]
+pub enum BrokerReceiver {
+    Unbounded(mpsc::UnboundedReceiver<BrokerRequest>),
+    Bounded(mpsc::Receiver<BrokerRequest>),
+}
+
+impl BrokerReceiver {
+    pub async fn recv(&mut self) -> Option<BrokerRequest> {
+        match self {
+            BrokerReceiver::Unbounded(rx) => rx.recv().await,
+            BrokerReceiver::Bounded(rx) => rx.recv().await,
+        }
+    }
+
+    /// How many requests the channel may hold, or `None` when unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        match self {
+            BrokerReceiver::Unbounded(_) => None,
+            BrokerReceiver::Bounded(rx) => Some(rx.max_capacity()),
+        }
+    }
+}
+
+impl From<mpsc::UnboundedReceiver<BrokerRequest>> for BrokerReceiver {
+    fn from(rx: mpsc::UnboundedReceiver<BrokerRequest>) -> Self {
+        BrokerReceiver::Unbounded(rx)
+    }
+}
+
+impl From<mpsc::Receiver<BrokerRequest>> for BrokerReceiver {
+    fn from(rx: mpsc::Receiver<BrokerRequest>) -> Self {
+        BrokerReceiver::Bounded(rx)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum RequestSender {
+    Unbounded(mpsc::UnboundedSender<BrokerRequest>),
+    Bounded(mpsc::Sender<BrokerRequest>),
+}
+
+/// Source of navigate correlation ids, unique within the process.
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Clone, Debug)]
 pub struct BrokerHandle {
-    tx: mpsc::UnboundedSender<BrokerRequest>,
+    tx: RequestSender,
 }
 
 impl BrokerHandle {
     pub fn new(tx: mpsc::UnboundedSender<BrokerRequest>) -> Self {
-        Self { tx }
+        Self {
+            tx: RequestSender::Unbounded(tx),
+        }
+    }
+
+    /// Handle backed by a channel holding at most `capacity` queued requests.
+    /// Once full, requests block the calling thread until the service drains
+    /// one. Pass the returned receiver to the service loop. A capacity of 0
+    /// is rejected: a channel must be able to hold at least one request.
+    pub fn bounded(capacity: usize) -> Result<(Self, mpsc::Receiver<BrokerRequest>)> {
+        anyhow::ensure!(capacity > 0, "broker request channel capacity must be at least 1");
+        let (tx, rx) = mpsc::channel(capacity);
+        Ok((
+            Self {
+                tx: RequestSender::Bounded(tx),
+            },
+            rx,
+        ))
+    }
+
+    fn send(&self, request: BrokerRequest) -> Result<()> {
+        let sent = match &self.tx {
+            RequestSender::Unbounded(tx) => tx.send(request).is_ok(),
+            RequestSender::Bounded(tx) => tx.blocking_send(request).is_ok(),
+        };
+        if !sent {
+            return Err(ChannelClosed { channel: "request" }.into());
+        }
+        Ok(())
     }
 
     fn round_trip<T, F>(&self, build_request: F) -> Result<T>
+    where
+        F: FnOnce(oneshot::Sender<Result<T>>) -> BrokerRequest,
+    {
+        self.send_pending(build_request)?.wait()
+    }
+
+    fn send_pending<T, F>(&self, build_request: F) -> Result<PendingReply<T>>
     where
         F: FnOnce(oneshot::Sender<Result<T>>) -> BrokerRequest,
     {
         let (reply_tx, reply_rx) = oneshot::channel();
-        self.tx
-            .send(build_request(reply_tx))
-            .map_err(|_| anyhow!("broker request channel closed"))?;
-        reply_rx
-            .blocking_recv()
-            .map_err(|_| anyhow!("broker reply channel closed"))?
+        self.send(build_request(reply_tx))?;
+        Ok(PendingReply { rx: reply_rx })
     }
 
     pub fn create_page(&self) -> Result<u32> {
         self.round_trip(|reply| BrokerRequest::CreatePage { reply })
     }
 
-    pub fn navigate(&self, page_id: u32, url: String, opts_json: String) -> Result<String> {
-        self.round_trip(|reply| BrokerRequest::Navigate {
+    pub fn create_page_with_state(&self, envelope: MigrationEnvelope) -> Result<u32> {
+        self.round_trip(|reply| BrokerRequest::CreatePageWithState { envelope, reply })
+    }
+
+    pub fn navigate(&self, page_id: u32, url: String, opts_json: String) -> Result<String, NavigateError> {
+        Ok(self.navigate_pending(page_id, url, opts_json)?.wait()?)
+    }
+
+    /// Load `url` on every engine kind side by side; see
+    /// [`BrokerRequest::NavigateCompare`] for the reply.
+    pub fn navigate_compare(&self, url: String, opts_json: String) -> Result<String> {
+        self.round_trip(|reply| BrokerRequest::NavigateCompare { url, opts_json, reply })
+    }
+
+    /// Queue a navigate without waiting for it to finish.
+    pub fn navigate_pending(&self, page_id: u32, url: String, opts_json: String) -> Result<PendingReply<String>> {
+        let correlation_id = NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(target: "pneuma_broker", page_id, correlation_id, url = %url, "navigate requested");
+        self.send_pending(|reply| BrokerRequest::Navigate {
             page_id,
             url,
             opts_json,
+            correlation_id,
             reply,
         })
     }
 
     pub fn evaluate(&self, page_id: u32, script: String) -> Result<String> {
-        self.round_trip(|reply| BrokerRequest::Evaluate {
+        self.evaluate_pending(page_id, script)?.wait()
+    }
+
+    /// Queue an evaluate without waiting for its result.
+    pub fn evaluate_pending(&self, page_id: u32, script: String) -> Result<PendingReply<String>> {
+        self.send_pending(|reply| BrokerRequest::Evaluate {
+            page_id,
+            script,
+            reply,
+        })
+    }
+
+    /// Like [`evaluate`](Self::evaluate), but returns the result already parsed
+    /// instead of as JSON text.
+    pub fn evaluate_value(&self, page_id: u32, script: String) -> Result<Value> {
+        let raw = self.evaluate(page_id, script)?;
+        serde_json::from_str(&raw).with_context(|| format!("evaluate result was not valid JSON: {raw}"))
+    }
+
+    /// Evaluate `script`, leaving the (JSON) result in the broker. Read it with
+    /// [`read_chunk`](Self::read_chunk) and release it with
+    /// [`free_result`](Self::free_result).
+    pub fn evaluate_stored(&self, page_id: u32, script: String) -> Result<StoredResult> {
+        self.round_trip(|reply| BrokerRequest::EvaluateStored {
             page_id,
             script,
             reply,
         })
     }
 
+    pub fn read_chunk(&self, handle: u32, offset: usize, len: usize) -> Result<ResultChunk> {
+        self.round_trip(|reply| BrokerRequest::ReadChunk {
+            handle,
+            offset,
+            len,
+            reply,
+        })
+    }
+
+    pub fn free_result(&self, handle: u32) -> Result<bool> {
+        self.round_trip(|reply| BrokerRequest::FreeResult { handle, reply })
+    }
+
+    pub fn evaluate_all(&self, script: String) -> Result<Vec<(u32, Result<String>)>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.send(BrokerRequest::EvaluateAll { script, reply: reply_tx })?;
+        Ok(reply_rx.blocking_recv().map_err(|_| ChannelClosed { channel: "reply" })?)
+    }
+
+    pub fn add_init_script(&self, script: String) -> Result<()> {
+        self.round_trip(|reply| BrokerRequest::AddInitScript { script, reply })
+    }
+
     pub fn screenshot(&self, page_id: u32) -> Result<Vec<u8>> {
         self.round_trip(|reply| BrokerRequest::Screenshot { page_id, reply })
     }
 
-    pub fn close_browser(&self) -> Result<()> {
+    pub fn screenshot_element(&self, page_id: u32, selector: String) -> Result<Vec<u8>> {
+        self.round_trip(|reply| BrokerRequest::ScreenshotElement {
+            page_id,
+            selector,
+            reply,
+        })
+    }
+
+    pub fn get_cookies(&self, page_id: u32) -> Result<Vec<MigrationCookie>> {
+        self.round_trip(|reply| BrokerRequest::GetCookies { page_id, reply })
+    }
+
+    pub fn close_page(&self, page_id: u32) -> Result<()> {
+        self.round_trip(|reply| BrokerRequest::ClosePage { page_id, reply })
+    }
+
+    pub fn close_browser(&self) -> Result<ShutdownReport> {
         self.round_trip(|reply| BrokerRequest::CloseBrowser { reply })
     }
 
-    pub fn shutdown(&self) -> Result<()> {
+    pub fn session_state(&self) -> Result<MigratableSessionState> {
+        self.round_trip(|reply| BrokerRequest::SessionState { reply })
+    }
+
+    pub fn rollback(&self) -> Result<bool> {
+        self.round_trip(|reply| BrokerRequest::Rollback { reply })
+    }
+
+    pub fn reset_engine(&self) -> Result<()> {
+        self.round_trip(|reply| BrokerRequest::ResetEngine { reply })
+    }
+
+    pub fn shutdown(&self) -> Result<ShutdownReport> {
         self.round_trip(|reply| BrokerRequest::Shutdown { reply })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BrokerHandle, BrokerRequest, NavigateError};
+    use crate::policy::NavigationBlocked;
+    use pneuma_engines::EngineError;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    /// Answer every `Evaluate` with `raw` from a plain thread, as the service would.
+    fn handle_answering_evaluate(raw: &'static str) -> BrokerHandle {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Evaluate { reply, .. } = req {
+                    let _ = reply.send(Ok(raw.to_string()));
+                }
+            }
+        });
+        BrokerHandle::new(tx)
+    }
+
+    #[test]
+    fn evaluate_value_returns_parsed_object() {
+        let handle = handle_answering_evaluate(r#"{"title":"Example Domain","links":[1,2]}"#);
+        let value = handle
+            .evaluate_value(1, "({ title: document.title })".into())
+            .expect("evaluate_value should succeed");
+        assert_eq!(value, json!({ "title": "Example Domain", "links": [1, 2] }));
+    }
+
+    #[test]
+    fn evaluate_value_does_not_double_encode_strings() {
+        let handle = handle_answering_evaluate(r#""Example Domain""#);
+        let value = handle
+            .evaluate_value(1, "document.title".into())
+            .expect("evaluate_value should succeed");
+        assert_eq!(value.as_str(), Some("Example Domain"));
+    }
+
+    /// Answer navigates in turn with the errors `replies` builds; `None`
+    /// drops the reply unanswered.
+    fn handle_failing_navigates(replies: Vec<Option<fn() -> anyhow::Error>>) -> BrokerHandle {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            let mut replies = replies.into_iter();
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Navigate { reply, .. } = req {
+                    match replies.next().flatten() {
+                        Some(error) => drop(reply.send(Err(error()))),
+                        None => drop(reply),
+                    }
+                }
+            }
+        });
+        BrokerHandle::new(tx)
+    }
+
+    fn navigate(handle: &BrokerHandle) -> NavigateError {
+        handle
+            .navigate(1, "https://example.com/".into(), "{}".into())
+            .expect_err("navigate should fail")
+    }
+
+    #[test]
+    fn navigate_reports_a_closed_broker_as_channel_closed() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        drop(rx);
+        let error = navigate(&BrokerHandle::new(tx));
+        assert!(matches!(error, NavigateError::ChannelClosed), "{error:?}");
+        assert!(!error.is_retryable());
+
+        let error = navigate(&handle_failing_navigates(vec![None]));
+        assert!(matches!(error, NavigateError::ChannelClosed), "dropped reply: {error:?}");
+        assert_eq!(error.kind(), "channel_closed");
+    }
+
+    #[test]
+    fn navigate_classifies_engine_policy_and_other_errors() {
+        let handle = handle_failing_navigates(vec![
+            Some(|| {
+                anyhow::Error::from(EngineError::WebDriver {
+                    status: 503,
+                    message: "busy".into(),
+                })
+                .context("navigate failed")
+            }),
+            Some(|| EngineError::Unavailable("servo exited".into()).into()),
+            Some(|| EngineError::Timeout("navigate timed out after 60s".into()).into()),
+            Some(|| {
+                NavigationBlocked {
+                    url: "https://ads.example/".into(),
+                    reason: "denylisted".into(),
+                }
+                .into()
+            }),
+            Some(|| anyhow::anyhow!("invalid navigate options")),
+        ]);
+
+        let error = navigate(&handle);
+        assert!(matches!(error, NavigateError::Engine(EngineError::WebDriver { status: 503, .. })));
+        assert!(error.is_retryable(), "a 5xx is transient");
+        let error = navigate(&handle);
+        assert!(matches!(error, NavigateError::Engine(EngineError::Unavailable(_))));
+        assert!(!error.is_retryable());
+        let error = navigate(&handle);
+        assert!(matches!(error, NavigateError::Timeout(ref message) if message.contains("60s")));
+        assert!(error.is_retryable());
+        let error = navigate(&handle);
+        assert_eq!((error.kind(), error.is_retryable()), ("policy_blocked", false));
+        assert!(error.to_string().contains("denylisted"), "{error}");
+        let error = navigate(&handle);
+        assert_eq!((error.kind(), error.to_string().as_str()), ("other", "invalid navigate options"));
+    }
+
+    #[test]
+    fn navigate_requests_carry_a_fresh_correlation_id() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let answerer = std::thread::spawn(move || {
+            let mut ids = Vec::new();
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Navigate { correlation_id, reply, .. } = req {
+                    ids.push(correlation_id);
+                    let _ = reply.send(Ok("{}".into()));
+                }
+            }
+            ids
+        });
+        let handle = BrokerHandle::new(tx);
+        for _ in 0..2 {
+            handle
+                .navigate(1, "https://example.com/".into(), "{}".into())
+                .expect("navigate should succeed");
+        }
+        drop(handle);
+
+        let ids = answerer.join().unwrap();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.iter().all(|&id| id != 0), "{ids:?}");
+        assert_ne!(ids[0], ids[1], "every navigate gets its own id");
+    }
+
+    #[test]
+    fn bounded_handle_blocks_senders_until_requests_drain() {
+        let (handle, mut rx) = BrokerHandle::bounded(2).expect("capacity is positive");
+        let senders: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                std::thread::spawn(move || handle.create_page())
+            })
+            .collect();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while rx.len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(rx.len(), 2, "only `capacity` requests may be queued");
+
+        for page_id in 1..=4 {
+            match rx.blocking_recv().expect("blocked senders should resume") {
+                BrokerRequest::CreatePage { reply } => {
+                    let _ = reply.send(Ok(page_id));
+                }
+                other => panic!("unexpected request: {other:?}"),
+            }
+        }
+        let mut page_ids: Vec<u32> = senders
+            .into_iter()
+            .map(|sender| sender.join().unwrap().expect("create_page should succeed"))
+            .collect();
+        page_ids.sort_unstable();
+        assert_eq!(page_ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn bounded_handle_rejects_a_zero_capacity() {
+        let error = BrokerHandle::bounded(0).expect_err("capacity 0 must be rejected");
+        assert!(error.to_string().contains("at least 1"), "{error}");
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Read every `.js` file directly inside `dir`, in file-name order, for
+/// registration as init scripts. Files that cannot be read (or are not
+/// UTF-8) are logged and skipped; only an unreadable `dir` is an error.
+pub fn load_init_scripts(dir: &Path) -> Result<Vec<String>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read init script directory {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| match entry {
+            Ok(entry) => Some(entry.path()),
+            Err(error) => {
+                tracing::warn!(
+                    target: "pneuma_broker",
+                    dir = %dir.display(),
+                    error = %error,
+                    "failed to list init script directory entry; skipping"
+                );
+                None
+            }
+        })
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .collect();
+    paths.sort();
+
+    let mut scripts = Vec::with_capacity(paths.len());
+    for path in paths {
+        match std::fs::read_to_string(&path) {
+            Ok(script) => {
+                tracing::info!(target: "pneuma_broker", path = %path.display(), "loaded init script");
+                scripts.push(script);
+            }
+            Err(error) => {
+                tracing::warn!(
+                    target: "pneuma_broker",
+                    path = %path.display(),
+                    error = %error,
+                    "failed to read init script; skipping"
+                );
+            }
+        }
+    }
+    Ok(scripts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_init_scripts;
+
+    #[test]
+    fn loads_js_files_in_name_order_and_skips_unreadable_ones() {
+        let dir = std::env::temp_dir().join(format!("pneuma-init-scripts-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("20-second.js"), "window.second = true;").unwrap();
+        std::fs::write(dir.join("10-first.js"), "window.first = true;").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a script").unwrap();
+        std::fs::write(dir.join("15-binary.js"), [0xff, 0xfe, 0x00]).unwrap();
+        std::fs::create_dir(dir.join("30-dir.js")).unwrap();
+
+        let scripts = load_init_scripts(&dir).expect("directory should be readable");
+        assert_eq!(scripts, vec!["window.first = true;", "window.second = true;"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(load_init_scripts(&dir).is_err());
+    }
+}
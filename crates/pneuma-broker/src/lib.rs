@@ -1,9 +1,18 @@
 pub mod broker;
+pub mod clock;
 pub mod confidence;
+pub mod config;
+pub mod diagnostics;
+pub mod endpoint_pool;
 pub mod engine_factory;
+pub mod events;
 pub mod handle;
+pub mod init_scripts;
+pub mod metrics;
 pub mod migration;
+pub mod policy;
+pub mod result_store;
 pub mod service;
 
 pub use broker::Broker;
-pub use handle::{BrokerHandle, BrokerRequest};
+pub use handle::{BrokerHandle, BrokerReceiver, BrokerRequest, PendingReply};
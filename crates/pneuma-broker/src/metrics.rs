@@ -0,0 +1,185 @@
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) for navigate and handoff latency buckets.
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Confidence scores bucket in 0.1 steps.
+const SCORE_BUCKETS: &[f64] = &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+/// Fixed-bucket histogram rendered as a Prometheus `histogram`.
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    state: Mutex<HistogramState>,
+}
+
+#[derive(Debug, Default)]
+struct HistogramState {
+    /// Per-bucket (non-cumulative) counts; the last slot is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            state: Mutex::new(HistogramState {
+                counts: vec![0; bounds.len() + 1],
+                ..HistogramState::default()
+            }),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        let slot = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.counts[slot] += 1;
+        state.sum += value;
+        state.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).count
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&state.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", state.count);
+        let _ = writeln!(out, "{name}_sum {}", state.sum);
+        let _ = writeln!(out, "{name}_count {}", state.count);
+    }
+}
+
+/// Counters and distributions recorded by the service loop, shared with
+/// whatever exposes them (serve mode's `/metrics`).
+#[derive(Debug)]
+pub struct BrokerMetrics {
+    pub navigations: AtomicU64,
+    pub navigation_failures: AtomicU64,
+    pub escalations: AtomicU64,
+    pub escalation_failures: AtomicU64,
+    pub navigate_duration: Histogram,
+    pub handoff_duration: Histogram,
+    pub confidence_score: Histogram,
+}
+
+impl Default for BrokerMetrics {
+    fn default() -> Self {
+        Self {
+            navigations: AtomicU64::new(0),
+            navigation_failures: AtomicU64::new(0),
+            escalations: AtomicU64::new(0),
+            escalation_failures: AtomicU64::new(0),
+            navigate_duration: Histogram::new(LATENCY_BUCKETS),
+            handoff_duration: Histogram::new(LATENCY_BUCKETS),
+            confidence_score: Histogram::new(SCORE_BUCKETS),
+        }
+    }
+}
+
+impl BrokerMetrics {
+    pub fn record_navigate(&self, elapsed: Duration, ok: bool) {
+        self.navigations.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.navigation_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.navigate_duration.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_handoff(&self, elapsed: Duration, ok: bool) {
+        self.escalations.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.escalation_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.handoff_duration.observe(elapsed.as_secs_f64());
+    }
+
+    /// Scores are `f32`; widening noise is rounded off so e.g. `0.4` lands in
+    /// the `le="0.4"` bucket rather than the next one.
+    pub fn record_score(&self, overall: f32) {
+        self.confidence_score.observe((f64::from(overall) * 1e6).round() / 1e6);
+    }
+
+    /// Everything in Prometheus text exposition format (version 0.0.4).
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (name, help, counter) in [
+            ("pneuma_navigations_total", "Navigations handled.", &self.navigations),
+            ("pneuma_navigation_failures_total", "Navigations that returned an error.", &self.navigation_failures),
+            ("pneuma_escalations_total", "Escalation handoffs attempted.", &self.escalations),
+            ("pneuma_escalation_failures_total", "Handoffs that failed or timed out.", &self.escalation_failures),
+        ] {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} counter");
+            let _ = writeln!(out, "{name} {}", counter.load(Ordering::Relaxed));
+        }
+        self.navigate_duration.render(
+            &mut out,
+            "pneuma_navigate_duration_seconds",
+            "Time for the engine to navigate, before any escalation.",
+        );
+        self.handoff_duration.render(
+            &mut out,
+            "pneuma_handoff_duration_seconds",
+            "Time for an escalation handoff, successful or not.",
+        );
+        self.confidence_score.render(&mut out, "pneuma_confidence_score", "Overall confidence score per navigation.");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BrokerMetrics;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_counters_and_populated_histograms() {
+        let metrics = BrokerMetrics::default();
+        metrics.record_navigate(Duration::from_millis(300), true);
+        metrics.record_navigate(Duration::from_secs(45), false);
+        metrics.record_handoff(Duration::from_millis(800), true);
+        metrics.record_score(0.35);
+        metrics.record_score(0.4);
+        metrics.record_score(0.92);
+
+        let text = metrics.render_prometheus();
+        for line in [
+            "# TYPE pneuma_navigations_total counter",
+            "pneuma_navigations_total 2",
+            "pneuma_navigation_failures_total 1",
+            "pneuma_escalations_total 1",
+            "pneuma_escalation_failures_total 0",
+            "# TYPE pneuma_navigate_duration_seconds histogram",
+            "pneuma_navigate_duration_seconds_bucket{le=\"0.25\"} 0",
+            "pneuma_navigate_duration_seconds_bucket{le=\"0.5\"} 1",
+            "pneuma_navigate_duration_seconds_bucket{le=\"30\"} 1",
+            "pneuma_navigate_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "pneuma_navigate_duration_seconds_count 2",
+            "pneuma_handoff_duration_seconds_bucket{le=\"1\"} 1",
+            "pneuma_confidence_score_bucket{le=\"0.3\"} 0",
+            "pneuma_confidence_score_bucket{le=\"0.4\"} 2",
+            "pneuma_confidence_score_bucket{le=\"0.9\"} 2",
+            "pneuma_confidence_score_bucket{le=\"1\"} 3",
+            "pneuma_confidence_score_count 3",
+        ] {
+            assert!(text.lines().any(|rendered| rendered == line), "missing `{line}` in:\n{text}");
+        }
+    }
+}
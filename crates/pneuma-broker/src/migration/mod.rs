@@ -1,3 +1,6 @@
 pub mod state;
+pub mod store;
 
+pub use pneuma_engines::{MigrationCookie, MigrationEnvelope};
 pub use state::MigratableSessionState;
+pub use store::{FileStateStore, StateStore};
@@ -1,7 +1,10 @@
 use pneuma_engines::EngineKind;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Durable record of where a broker session currently lives. The service loop
+/// keeps one per browser, updating it on navigate, escalation, rollback and
+/// reset, and persists it through the configured [`StateStore`](super::StateStore).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MigratableSessionState {
     pub session_id: String,
     pub active_engine: EngineKind,
@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use pneuma_engines::MigrationEnvelope;
+
+use super::MigratableSessionState;
+
+/// Durable storage for captured [`MigrationEnvelope`]s and the
+/// [`MigratableSessionState`] record, both keyed by its `session_id`.
+///
+/// Deployments plug in their own backend (Redis, S3, ...);
+/// [`FileStateStore`] is the built-in default.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Store `envelope` under `session_id`, replacing any previous value.
+    async fn put(&self, session_id: &str, envelope: &MigrationEnvelope) -> Result<()>;
+
+    /// Fetch the envelope stored under `session_id`; `Ok(None)` when absent.
+    async fn get(&self, session_id: &str) -> Result<Option<MigrationEnvelope>>;
+
+    /// Store the session record, replacing any previous value.
+    async fn put_session(&self, state: &MigratableSessionState) -> Result<()>;
+
+    /// Fetch the session record for `session_id`; `Ok(None)` when absent.
+    async fn get_session(&self, session_id: &str) -> Result<Option<MigratableSessionState>>;
+}
+
+/// Stores each envelope as `<dir>/<session_id>.json` and each session record
+/// as `<dir>/<session_id>.session.json`.
+#[derive(Debug, Clone)]
+pub struct FileStateStore {
+    dir: PathBuf,
+}
+
+impl FileStateStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store rooted at `PNEUMA_STATE_DIR`, or `None` when persistence is not configured.
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("PNEUMA_STATE_DIR").ok()?;
+        let dir = dir.trim();
+        (!dir.is_empty()).then(|| Self::new(dir))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, session_id: &str, suffix: &str) -> Result<PathBuf> {
+        let valid = !session_id.is_empty()
+            && session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !session_id.starts_with('.');
+        if !valid {
+            bail!("session id `{session_id}` is not usable as a file name");
+        }
+        Ok(self.dir.join(format!("{session_id}{suffix}")))
+    }
+
+    async fn write_json<T: serde::Serialize>(&self, path: &Path, value: &T) -> Result<()> {
+        let json = serde_json::to_vec_pretty(value).context("failed to encode state")?;
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("failed to create state directory {}", self.dir.display()))?;
+        // Write-then-rename so readers never observe a half-written file.
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, json)
+            .await
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        tokio::fs::rename(&tmp, path)
+            .await
+            .with_context(|| format!("failed to move state into {}", path.display()))
+    }
+
+    async fn read_json<T: serde::de::DeserializeOwned>(&self, path: &Path) -> Result<Option<T>> {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        let value = serde_json::from_slice(&bytes)
+            .with_context(|| format!("failed to decode {}", path.display()))?;
+        Ok(Some(value))
+    }
+}
+
+#[async_trait]
+impl StateStore for FileStateStore {
+    async fn put(&self, session_id: &str, envelope: &MigrationEnvelope) -> Result<()> {
+        let path = self.path_for(session_id, ".json")?;
+        self.write_json(&path, envelope).await
+    }
+
+    async fn get(&self, session_id: &str) -> Result<Option<MigrationEnvelope>> {
+        let path = self.path_for(session_id, ".json")?;
+        self.read_json(&path).await
+    }
+
+    async fn put_session(&self, state: &MigratableSessionState) -> Result<()> {
+        let path = self.path_for(&state.session_id, ".session.json")?;
+        self.write_json(&path, state).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<MigratableSessionState>> {
+        let path = self.path_for(session_id, ".session.json")?;
+        self.read_json(&path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileStateStore, StateStore};
+    use crate::migration::MigratableSessionState;
+    use pneuma_engines::{EngineKind, LocalStorageEntry, MigrationCookie, MigrationEnvelope};
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("pneuma-state-store-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn file_store_round_trips_envelope() {
+        let dir = scratch_dir("round-trip");
+        let store = FileStateStore::new(&dir);
+        let envelope = MigrationEnvelope {
+            source_engine: EngineKind::Servo,
+            captured_at_ms: 1_700_000_000_000,
+            current_url: Some("https://example.com/cart".into()),
+            cookies: vec![MigrationCookie {
+                name: "sid".into(),
+                value: "abc123".into(),
+                domain: Some("example.com".into()),
+                path: Some("/".into()),
+                secure: Some(true),
+                http_only: Some(true),
+                expiry: None,
+                same_site: Some("Lax".into()),
+            }],
+            local_storage: vec![LocalStorageEntry {
+                key: "theme".into(),
+                value: "dark".into(),
+                origin: Some("https://example.com".into()),
+            }],
+            local_storage_coerced: 0,
+            local_storage_skipped: 0,
+            cookies_skipped: 0,
+        };
+
+        store.put("session-1", &envelope).await.expect("put should succeed");
+        let loaded = store
+            .get("session-1")
+            .await
+            .expect("get should succeed")
+            .expect("envelope should be present");
+
+        assert_eq!(loaded.current_url, envelope.current_url);
+        assert_eq!(loaded.captured_at_ms, envelope.captured_at_ms);
+        assert_eq!(loaded.cookies.len(), 1);
+        assert_eq!(loaded.cookies[0].value, "abc123");
+        assert_eq!(loaded.local_storage[0].value, "dark");
+        assert_eq!(loaded.local_storage[0].origin.as_deref(), Some("https://example.com"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn file_store_keeps_session_record_beside_envelope() {
+        let dir = scratch_dir("session-record");
+        let store = FileStateStore::new(&dir);
+        let state = MigratableSessionState {
+            session_id: "session-2".into(),
+            active_engine: EngineKind::Ladybird,
+            last_url: Some("https://example.com/".into()),
+        };
+
+        store.put_session(&state).await.expect("put_session should succeed");
+        assert_eq!(store.get_session("session-2").await.expect("get_session"), Some(state));
+        assert!(store.get("session-2").await.expect("get").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn missing_session_is_none() {
+        let dir = scratch_dir("missing");
+        let store = FileStateStore::new(&dir);
+        assert!(store.get("never-stored").await.expect("get should succeed").is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_session_ids_that_escape_the_directory() {
+        let store = FileStateStore::new(scratch_dir("escape"));
+        assert!(store.get("../etc/passwd").await.is_err());
+        assert!(store.get("").await.is_err());
+    }
+}
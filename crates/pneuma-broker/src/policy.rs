@@ -0,0 +1,134 @@
+/// Outcome of checking a navigation against a [`NavigatePolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    /// Navigate to this URL instead.
+    Rewrite(String),
+    /// Refuse the navigation; the reason is reported to the caller.
+    Block(String),
+}
+
+/// Consulted by the service loop before a navigation reaches an engine. An
+/// escalation re-navigates to the already-checked (possibly rewritten) URL.
+pub trait NavigatePolicy: Send + Sync {
+    fn check(&self, url: &str) -> PolicyDecision;
+}
+
+/// Error returned for a navigation the policy blocked. The engine is not
+/// touched.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("navigation to {url} blocked by policy: {reason}")]
+pub struct NavigationBlocked {
+    pub url: String,
+    pub reason: String,
+}
+
+/// The default policy: every URL is allowed unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl NavigatePolicy for AllowAll {
+    fn check(&self, _url: &str) -> PolicyDecision {
+        PolicyDecision::Allow
+    }
+}
+
+/// Blocks navigation to the listed hosts and their subdomains.
+#[derive(Debug, Clone, Default)]
+pub struct HostBlocklist {
+    hosts: Vec<String>,
+}
+
+impl HostBlocklist {
+    /// Hosts are matched case-insensitively; a leading `.` is ignored, and
+    /// internationalized names match their punycode form.
+    pub fn new<I, S>(hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let hosts = hosts
+            .into_iter()
+            .filter_map(|host| {
+                let host = host.as_ref().trim().trim_start_matches('.');
+                (!host.is_empty()).then(|| url_host(&format!("http://{host}/")).unwrap_or_else(|| host.to_lowercase()))
+            })
+            .collect();
+        Self { hosts }
+    }
+}
+
+impl NavigatePolicy for HostBlocklist {
+    fn check(&self, url: &str) -> PolicyDecision {
+        let Some(host) = url_host(url) else {
+            return PolicyDecision::Allow;
+        };
+        let blocked = self.hosts.iter().find(|blocked| {
+            host == **blocked
+                || host
+                    .strip_suffix(blocked.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        });
+        match blocked {
+            Some(blocked) => PolicyDecision::Block(format!("host {host} is on the blocklist ({blocked})")),
+            None => PolicyDecision::Allow,
+        }
+    }
+}
+
+/// Host of `url` as a browser resolves it: lowercased, percent-decoded and
+/// punycoded, without userinfo, port, IPv6 brackets or a trailing dot.
+fn url_host(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches('[').trim_end_matches([']', '.']);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{url_host, AllowAll, HostBlocklist, NavigatePolicy, PolicyDecision};
+
+    #[test]
+    fn extracts_host_from_urls() {
+        assert_eq!(url_host("https://Example.COM/path").as_deref(), Some("example.com"));
+        assert_eq!(url_host("http://user:pw@ads.example.com:8080/?q").as_deref(), Some("ads.example.com"));
+        assert_eq!(url_host("http://[::1]:9000/").as_deref(), Some("::1"));
+        assert_eq!(url_host("https://example.com.#top").as_deref(), Some("example.com"));
+        assert_eq!(url_host("about:blank"), None);
+        assert_eq!(url_host("not a url"), None);
+    }
+
+    #[test]
+    fn extracts_the_host_a_browser_would_connect_to() {
+        // Browsers treat `\` as `/`, so the host is `blocked.example`.
+        assert_eq!(url_host(r"https://blocked.example\@ok.com/").as_deref(), Some("blocked.example"));
+        assert_eq!(url_host("https://blocked%2Eexample/").as_deref(), Some("blocked.example"));
+        assert_eq!(url_host("https://BÜCHER.example/").as_deref(), Some("xn--bcher-kva.example"));
+    }
+
+    #[test]
+    fn blocklist_sees_through_url_obfuscation() {
+        let policy = HostBlocklist::new(["blocked.example", "bücher.example"]);
+        for url in [
+            r"https://blocked.example\@ok.com/",
+            "https://blocked%2Eexample/",
+            "https://sub.BLOCKED.example./",
+            "https://xn--bcher-kva.example/",
+            "https://Bücher.example/",
+        ] {
+            assert!(matches!(policy.check(url), PolicyDecision::Block(_)), "{url} must be blocked");
+        }
+        assert_eq!(policy.check("https://ok.com/?u=blocked.example"), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn blocklist_blocks_hosts_and_subdomains_only() {
+        let policy = HostBlocklist::new(["tracker.example", ".Ads.Net"]);
+        assert!(matches!(policy.check("https://tracker.example/"), PolicyDecision::Block(_)));
+        assert!(matches!(policy.check("https://cdn.ads.net/x.js"), PolicyDecision::Block(_)));
+        assert_eq!(policy.check("https://notads.net/"), PolicyDecision::Allow);
+        assert_eq!(policy.check("https://example.com/?next=tracker.example"), PolicyDecision::Allow);
+        assert_eq!(policy.check("data:text/html,hi"), PolicyDecision::Allow);
+        assert_eq!(AllowAll.check("https://tracker.example/"), PolicyDecision::Allow);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+
+/// A result kept broker-side, to be read back with [`ResultStore::read_chunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StoredResult {
+    pub handle: u32,
+    /// Length of the stored text in bytes.
+    pub len: usize,
+}
+
+/// One slice of a stored result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResultChunk {
+    pub data: String,
+    /// Byte offset to request next; equals the total length once done.
+    pub next_offset: usize,
+    pub done: bool,
+}
+
+/// Large evaluate results held until the script reads and frees them, so a
+/// multi-megabyte payload crosses into the script a chunk at a time instead
+/// of as one string.
+#[derive(Debug, Default)]
+pub struct ResultStore {
+    results: HashMap<u32, String>,
+    next_handle: u32,
+}
+
+impl ResultStore {
+    pub fn insert(&mut self, result: String) -> StoredResult {
+        self.next_handle = self.next_handle.wrapping_add(1);
+        while self.results.contains_key(&self.next_handle) {
+            self.next_handle = self.next_handle.wrapping_add(1);
+        }
+        let stored = StoredResult {
+            handle: self.next_handle,
+            len: result.len(),
+        };
+        self.results.insert(stored.handle, result);
+        stored
+    }
+
+    /// Up to `len` bytes starting at byte `offset`. Chunks end on a character
+    /// boundary, so a chunk may be shorter than `len`, or one character longer
+    /// when `len` is smaller than the character at `offset`; continue from
+    /// `next_offset`.
+    pub fn read_chunk(&self, handle: u32, offset: usize, len: usize) -> Result<ResultChunk> {
+        let text = self
+            .results
+            .get(&handle)
+            .ok_or_else(|| anyhow!("unknown or freed result handle {handle}"))?;
+        if offset > text.len() || !text.is_char_boundary(offset) {
+            bail!("offset {offset} is not a chunk boundary of result {handle}");
+        }
+        if len == 0 {
+            bail!("chunk length must be positive");
+        }
+        let mut end = offset.saturating_add(len).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == offset && offset < text.len() {
+            end = offset + text[offset..].chars().next().map_or(0, char::len_utf8);
+        }
+        Ok(ResultChunk {
+            data: text[offset..end].to_string(),
+            next_offset: end,
+            done: end == text.len(),
+        })
+    }
+
+    /// Drop a stored result. Returns false when the handle was unknown.
+    pub fn free(&mut self, handle: u32) -> bool {
+        self.results.remove(&handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResultStore;
+
+    fn read_all(store: &ResultStore, handle: u32, chunk_len: usize) -> (String, usize) {
+        let (mut text, mut offset, mut chunks) = (String::new(), 0, 0);
+        loop {
+            let chunk = store.read_chunk(handle, offset, chunk_len).expect("chunk should read");
+            text.push_str(&chunk.data);
+            offset = chunk.next_offset;
+            chunks += 1;
+            if chunk.done {
+                return (text, chunks);
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_reassemble_at_exact_and_ragged_boundaries() {
+        let mut store = ResultStore::default();
+        let stored = store.insert("abcdefghij".into());
+        assert_eq!(stored.len, 10);
+
+        assert_eq!(read_all(&store, stored.handle, 5), ("abcdefghij".into(), 2));
+        assert_eq!(read_all(&store, stored.handle, 3), ("abcdefghij".into(), 4));
+        assert_eq!(read_all(&store, stored.handle, 64), ("abcdefghij".into(), 1));
+
+        let last = store.read_chunk(stored.handle, 9, 5).unwrap();
+        assert_eq!((last.data.as_str(), last.next_offset, last.done), ("j", 10, true));
+        let past_end = store.read_chunk(stored.handle, 10, 5).unwrap();
+        assert_eq!((past_end.data.as_str(), past_end.done), ("", true));
+        assert!(store.read_chunk(stored.handle, 11, 5).is_err());
+        assert!(store.read_chunk(stored.handle, 0, 0).is_err());
+    }
+
+    #[test]
+    fn chunks_never_split_characters() {
+        let mut store = ResultStore::default();
+        let text = "h\u{e9}llo \u{1f600} w\u{f6}rld";
+        let stored = store.insert(text.into());
+
+        for chunk_len in 1..=6 {
+            assert_eq!(read_all(&store, stored.handle, chunk_len).0, text, "chunk_len {chunk_len}");
+        }
+        let first = store.read_chunk(stored.handle, 0, 2).unwrap();
+        assert_eq!((first.data.as_str(), first.next_offset), ("h", 1));
+        assert!(store.read_chunk(stored.handle, 2, 4).is_err(), "offset inside a character");
+    }
+
+    #[test]
+    fn freed_results_cannot_be_read() {
+        let mut store = ResultStore::default();
+        let first = store.insert("first".into());
+        let second = store.insert("second".into());
+        assert_ne!(first.handle, second.handle);
+
+        assert!(store.free(first.handle));
+        assert!(!store.free(first.handle), "double free reports unknown handle");
+        assert!(store.read_chunk(first.handle, 0, 4).is_err());
+        assert_eq!(store.read_chunk(second.handle, 0, 64).unwrap().data, "second");
+        assert_eq!(store.len(), 1);
+    }
+}
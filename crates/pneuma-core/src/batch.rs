@@ -0,0 +1,215 @@
+//! `pneuma batch`: navigate a list of URLs one after another and print one
+//! JSON result per line. With a checkpoint file, each successful result is
+//! appended to it as soon as the URL completes, and a later run skips the
+//! URLs found there, so an interrupted crawl resumes where it stopped.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// The URLs in `input`, one per line; blank lines and `#` comments are skipped.
+pub fn read_urls(input: impl BufRead) -> Result<Vec<String>> {
+    let mut urls = Vec::new();
+    for line in input.lines() {
+        let line = line.context("failed to read URL list")?;
+        let url = line.trim();
+        if !url.is_empty() && !url.starts_with('#') {
+            urls.push(url.to_string());
+        }
+    }
+    Ok(urls)
+}
+
+/// Results of the URLs a batch has finished, kept as JSON lines in a file.
+/// Only successes are recorded, so failed URLs are retried on resume.
+pub struct Checkpoint {
+    path: PathBuf,
+    file: File,
+    done: HashMap<String, Value>,
+}
+
+impl Checkpoint {
+    /// Open `path`, creating it if missing, and load the results already in
+    /// it. A line torn by an interrupted write is skipped.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .with_context(|| format!("failed to open checkpoint {}", path.display()))?;
+        let mut text = String::new();
+        file.read_to_string(&mut text)
+            .with_context(|| format!("failed to read checkpoint {}", path.display()))?;
+
+        let mut done = HashMap::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<Value>(line)
+                .ok()
+                .and_then(|record| Some((record.get("url")?.as_str()?.to_string(), record)));
+            match record {
+                Some((url, record)) => {
+                    done.insert(url, record);
+                }
+                None => tracing::warn!(
+                    path = %path.display(),
+                    line = index + 1,
+                    "skipping unreadable checkpoint line"
+                ),
+            }
+        }
+        // Finish a torn last line so the next record starts on its own.
+        if !text.is_empty() && !text.ends_with('\n') {
+            writeln!(file).with_context(|| format!("failed to write checkpoint {}", path.display()))?;
+        }
+        tracing::info!(path = %path.display(), completed = done.len(), "loaded batch checkpoint");
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            done,
+        })
+    }
+
+    /// The recorded result for `url`, if it already completed.
+    pub fn get(&self, url: &str) -> Option<&Value> {
+        self.done.get(url)
+    }
+
+    /// Append `record` for `url` and flush it to disk before returning.
+    fn record(&mut self, url: &str, record: &Value) -> Result<()> {
+        writeln!(self.file, "{record}")
+            .and_then(|()| self.file.sync_data())
+            .with_context(|| format!("failed to write checkpoint {}", self.path.display()))?;
+        self.done.insert(url.to_string(), record.clone());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub navigated: usize,
+    /// Already in the checkpoint; reprinted without navigating.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Navigate every URL not yet in `checkpoint` with `navigate`, which returns
+/// the navigate metadata JSON, and write one record per URL to `out`:
+/// `{"url", "ok": true, "meta"}` or `{"url", "ok": false, "error"}`.
+pub async fn run_batch<F, Fut>(
+    urls: &[String],
+    mut checkpoint: Option<Checkpoint>,
+    mut navigate: F,
+    mut out: impl Write,
+) -> Result<BatchSummary>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut summary = BatchSummary::default();
+    for url in urls {
+        if let Some(record) = checkpoint.as_ref().and_then(|checkpoint| checkpoint.get(url)) {
+            writeln!(out, "{record}")?;
+            summary.skipped += 1;
+            continue;
+        }
+        let record = match navigate(url.clone()).await {
+            Ok(meta_json) => {
+                let meta = serde_json::from_str(&meta_json).unwrap_or(Value::String(meta_json));
+                serde_json::json!({ "url": url, "ok": true, "meta": meta })
+            }
+            Err(error) => {
+                tracing::warn!(url = %url, error = %error, "batch navigate failed");
+                summary.failed += 1;
+                serde_json::json!({ "url": url, "ok": false, "error": format!("{error:#}") })
+            }
+        };
+        summary.navigated += 1;
+        if let Some(checkpoint) = checkpoint.as_mut().filter(|_| record["ok"] == true) {
+            checkpoint.record(url, &record)?;
+        }
+        writeln!(out, "{record}")?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_urls, run_batch, BatchSummary, Checkpoint};
+    use std::cell::{Cell, RefCell};
+    use std::io::Write;
+
+    fn urls(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| format!("https://{name}.example/")).collect()
+    }
+
+    #[test]
+    fn url_list_skips_blanks_and_comments() {
+        let input = "https://a.example/\n\n  # later\n https://b.example/ \n";
+        assert_eq!(read_urls(input.as_bytes()).unwrap(), urls(&["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn resumed_batch_skips_completed_urls() {
+        let path = std::env::temp_dir().join(format!("pneuma-batch-checkpoint-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let all = urls(&["a", "b", "c", "d"]);
+        let calls = RefCell::new(Vec::new());
+        let b_fails = Cell::new(true);
+        let navigate = |url: String| {
+            calls.borrow_mut().push(url.clone());
+            let fail = b_fails.get() && url.contains("b.example");
+            async move {
+                if fail {
+                    anyhow::bail!("connection reset");
+                }
+                Ok(format!(r#"{{"ok":true,"title":"{url}"}}"#))
+            }
+        };
+
+        // First run stops after three URLs, the last record torn mid-write.
+        let mut out = Vec::new();
+        let summary = run_batch(&all[..3], Some(Checkpoint::open(&path).unwrap()), navigate, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(summary, BatchSummary { navigated: 3, skipped: 0, failed: 1 });
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(br#"{"url":"https://d.exa"#)
+            .unwrap();
+
+        calls.borrow_mut().clear();
+        b_fails.set(false);
+        let mut out = Vec::new();
+        let summary = run_batch(&all, Some(Checkpoint::open(&path).unwrap()), navigate, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(*calls.borrow(), urls(&["b", "d"]), "the failed URL is retried, the done ones skipped");
+        assert_eq!(summary, BatchSummary { navigated: 2, skipped: 2, failed: 0 });
+        let records: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 4);
+        assert!(records.iter().zip(&all).all(|(record, url)| record["url"] == *url && record["ok"] == true));
+
+        // Everything is done now, so a third run navigates nothing.
+        calls.borrow_mut().clear();
+        let summary = run_batch(&all, Some(Checkpoint::open(&path).unwrap()), navigate, std::io::sink())
+            .await
+            .unwrap();
+        assert_eq!((summary.skipped, calls.borrow().len()), (4, 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,385 @@
+//! Optional Chrome DevTools Protocol shim for serve mode, so existing CDP
+//! clients can drive pneuma over a WebSocket without bespoke code.
+//!
+//! Only this subset is understood; every other method gets a CDP error
+//! object (`-32601`) back:
+//!
+//! - `Page.navigate { url }` replies `{ frameId, loaderId }`, or also
+//!   `errorText` when the navigate fails, as Chrome does.
+//! - `Runtime.evaluate { expression }` replies `{ result: RemoteObject }`,
+//!   with `exceptionDetails` when the script throws. The expression runs
+//!   through a global `eval`, so statements and trailing semicolons work as
+//!   in the DevTools console. Results are always returned by value; a broker
+//!   or engine failure is a CDP error object instead.
+//! - `Page.captureScreenshot` replies `{ data }` with a base64 PNG; other
+//!   formats and clips are rejected with `-32602`.
+//!
+//! Each connection gets its own broker page, created on its first command
+//! and closed when the socket closes.
+//! There is no target discovery (`/json/*`), no events and no domains to
+//! enable: connect straight to `ws://127.0.0.1:<cdp-port>/`.
+
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use futures_util::{SinkExt, StreamExt};
+use pneuma_broker::handle::BrokerHandle;
+use pneuma_engines::EngineError;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+/// JSON-RPC codes Chrome uses for the errors the shim can return.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// A CDP error object: `{ "code": ..., "message": ... }`.
+#[derive(Debug, Clone, PartialEq)]
+struct CdpError {
+    code: i64,
+    message: String,
+}
+
+impl CdpError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Accept CDP WebSocket connections until the listener fails.
+pub async fn serve_cdp(listener: TcpListener, handle: BrokerHandle) -> Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await.context("failed to accept CDP connection")?;
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            if let Err(error) = session(stream, handle).await {
+                tracing::debug!(%peer, error = %error, "CDP session failed");
+            }
+        });
+    }
+}
+
+async fn session(stream: TcpStream, handle: BrokerHandle) -> Result<()> {
+    let mut socket = tokio_tungstenite::accept_async(stream)
+        .await
+        .context("CDP WebSocket handshake failed")?;
+    let mut page_id = None;
+    let result = async {
+        while let Some(message) = socket.next().await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                // Pings are answered by tungstenite; CDP never sends binary frames.
+                _ => continue,
+            };
+            let response = respond(&text, &handle, &mut page_id).await;
+            socket.send(Message::Text(response.to_string())).await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+    if let Some(page_id) = page_id {
+        release_page(handle, page_id).await;
+    }
+    result
+}
+
+/// Close the connection's page; nothing else can reach it once the socket is gone.
+async fn release_page(handle: BrokerHandle, page_id: u32) {
+    let closed = tokio::task::spawn_blocking(move || handle.close_page(page_id)).await;
+    if let Err(error) = closed.map_err(anyhow::Error::from).and_then(|closed| closed) {
+        tracing::debug!(page_id, error = %error, "failed to close CDP page");
+    }
+}
+
+/// Answer one CDP command frame, echoing its `id` and `sessionId`.
+async fn respond(frame: &str, handle: &BrokerHandle, page_id: &mut Option<u32>) -> Value {
+    let command: Value = match serde_json::from_str(frame) {
+        Ok(command @ Value::Object(_)) => command,
+        _ => return json!({ "error": error_object(&CdpError::new(PARSE_ERROR, "Message must be a valid JSON")) }),
+    };
+    let id = command.get("id").cloned().unwrap_or(Value::Null);
+    let method = command
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let params = command.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let handle = handle.clone();
+    let mut page = *page_id;
+    // Handle calls block until the service loop replies.
+    let outcome = match tokio::task::spawn_blocking(move || {
+        let outcome = dispatch(&handle, &mut page, &method, &params);
+        (outcome, page)
+    })
+    .await
+    {
+        Ok((outcome, page)) => {
+            *page_id = page;
+            outcome
+        }
+        Err(error) => Err(CdpError::new(SERVER_ERROR, error.to_string())),
+    };
+
+    let mut response = match outcome {
+        Ok(result) => json!({ "id": id, "result": result }),
+        Err(error) => json!({ "id": id, "error": error_object(&error) }),
+    };
+    if let Some(session_id) = command.get("sessionId") {
+        response["sessionId"] = session_id.clone();
+    }
+    response
+}
+
+/// The connection's page, created on first use.
+fn page(handle: &BrokerHandle, page_id: &mut Option<u32>) -> Result<u32, CdpError> {
+    if let Some(page_id) = *page_id {
+        return Ok(page_id);
+    }
+    let created = handle
+        .create_page()
+        .map_err(|error| CdpError::new(SERVER_ERROR, format!("failed to create a page: {error:#}")))?;
+    *page_id = Some(created);
+    Ok(created)
+}
+
+fn dispatch(handle: &BrokerHandle, page_id: &mut Option<u32>, method: &str, params: &Value) -> Result<Value, CdpError> {
+    match method {
+        "Page.navigate" => {
+            let url = string_param(params, "url")?;
+            let page_id = page(handle, page_id)?;
+            let frame_id = page_id.to_string();
+            Ok(match handle.navigate(page_id, url, "{}".into()) {
+                Ok(_) => json!({ "frameId": frame_id, "loaderId": frame_id }),
+                Err(error) => json!({ "frameId": frame_id, "loaderId": frame_id, "errorText": error.to_string() }),
+            })
+        }
+        "Runtime.evaluate" => {
+            let expression = string_param(params, "expression")?;
+            let page_id = page(handle, page_id)?;
+            // Indirect eval: global scope and completion-value semantics, so a
+            // trailing `;` or a statement list is fine.
+            let script = format!("(0, eval)({})", Value::String(expression));
+            match handle.evaluate_value(page_id, script) {
+                Ok(value) => Ok(json!({ "result": remote_object(value) })),
+                Err(error) if is_script_exception(&error) => {
+                    let text = format!("{error:#}");
+                    Ok(json!({
+                        "result": { "type": "object", "subtype": "error", "description": text },
+                        "exceptionDetails": { "exceptionId": 1, "text": text, "lineNumber": 0, "columnNumber": 0 },
+                    }))
+                }
+                Err(error) => Err(CdpError::new(SERVER_ERROR, format!("{error:#}"))),
+            }
+        }
+        "Page.captureScreenshot" => {
+            if params
+                .get("format")
+                .and_then(Value::as_str)
+                .is_some_and(|format| format != "png")
+            {
+                return Err(CdpError::new(INVALID_PARAMS, "only the png format is supported"));
+            }
+            if params.get("clip").is_some() {
+                return Err(CdpError::new(INVALID_PARAMS, "clip is not supported"));
+            }
+            let png = handle
+                .screenshot(page(handle, page_id)?)
+                .map_err(|error| CdpError::new(SERVER_ERROR, format!("{error:#}")))?;
+            Ok(json!({ "data": base64::engine::general_purpose::STANDARD.encode(png) }))
+        }
+        other => Err(CdpError::new(METHOD_NOT_FOUND, format!("'{other}' wasn't found"))),
+    }
+}
+
+fn string_param(params: &Value, name: &str) -> Result<String, CdpError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            CdpError::new(
+                INVALID_PARAMS,
+                format!("Invalid parameters: missing or invalid '{name}'"),
+            )
+        })
+}
+
+/// Whether an evaluate failed because the script threw, as opposed to the
+/// broker or engine failing to run it.
+fn is_script_exception(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<EngineError>(),
+            Some(EngineError::WebDriver { message, .. }) if message.starts_with("javascript error")
+        )
+    })
+}
+
+fn error_object(error: &CdpError) -> Value {
+    json!({ "code": error.code, "message": error.message })
+}
+
+/// A by-value CDP `RemoteObject` for a JSON evaluate result.
+fn remote_object(value: Value) -> Value {
+    match value {
+        Value::Null => json!({ "type": "object", "subtype": "null", "value": null }),
+        Value::Bool(_) => json!({ "type": "boolean", "value": value }),
+        Value::Number(ref number) => json!({ "type": "number", "value": value, "description": number.to_string() }),
+        Value::String(_) => json!({ "type": "string", "value": value }),
+        Value::Array(_) => json!({ "type": "object", "subtype": "array", "value": value }),
+        Value::Object(_) => json!({ "type": "object", "value": value }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pneuma_broker::handle::BrokerRequest;
+
+    /// A broker that creates page 7 and evaluates `6 * 7` to `42`; scripts
+    /// mentioning `throw` fail as the page would, `wedged` as the transport
+    /// would. Closed pages are sent to the returned receiver.
+    fn fake_broker() -> (BrokerHandle, tokio::sync::mpsc::UnboundedReceiver<u32>) {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let (closed_tx, closed_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(request) = rx.recv().await {
+                match request {
+                    BrokerRequest::CreatePage { reply } => drop(reply.send(Ok(7))),
+                    BrokerRequest::Evaluate { page_id, script, reply } => {
+                        assert_eq!(page_id, 7);
+                        let result = if script.contains("throw") {
+                            Err(EngineError::WebDriver {
+                                status: 500,
+                                message: "javascript error: boom".into(),
+                            }
+                            .into())
+                        } else if script.contains("wedged") {
+                            Err(EngineError::Transport("connection reset".into()).into())
+                        } else {
+                            assert!(script.contains("6 * 7"), "expression is evaluated: {script}");
+                            Ok("42".into())
+                        };
+                        drop(reply.send(result));
+                    }
+                    BrokerRequest::ClosePage { page_id, reply } => {
+                        let _ = closed_tx.send(page_id);
+                        drop(reply.send(Ok(())));
+                    }
+                    _ => panic!("unexpected broker request"),
+                }
+            }
+        });
+        (BrokerHandle::new(tx), closed_rx)
+    }
+
+    type Socket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>;
+
+    async fn connect_with_log() -> (Socket, tokio::sync::mpsc::UnboundedReceiver<u32>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (handle, closed) = fake_broker();
+        tokio::spawn(serve_cdp(listener, handle));
+        let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/")).await.unwrap();
+        (socket, closed)
+    }
+
+    async fn connect() -> Socket {
+        connect_with_log().await.0
+    }
+
+    async fn round_trip(socket: &mut Socket, frame: Value) -> Value {
+        socket.send(Message::Text(frame.to_string())).await.unwrap();
+        match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("expected a text frame, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn runtime_evaluate_replies_with_a_remote_object() {
+        let mut socket = connect().await;
+        let response = round_trip(
+            &mut socket,
+            json!({ "id": 3, "sessionId": "s1", "method": "Runtime.evaluate", "params": { "expression": "6 * 7" } }),
+        )
+        .await;
+        assert_eq!(
+            response,
+            json!({
+                "id": 3,
+                "sessionId": "s1",
+                "result": { "result": { "type": "number", "value": 42, "description": "42" } },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn unsupported_methods_get_a_cdp_error() {
+        let mut socket = connect().await;
+        let response = round_trip(&mut socket, json!({ "id": 1, "method": "Network.enable" })).await;
+        assert_eq!(
+            response,
+            json!({ "id": 1, "error": { "code": -32601, "message": "'Network.enable' wasn't found" } })
+        );
+
+        let response = round_trip(
+            &mut socket,
+            json!({ "id": 2, "method": "Runtime.evaluate", "params": {} }),
+        )
+        .await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn runtime_evaluate_accepts_a_trailing_semicolon() {
+        let mut socket = connect().await;
+        let response = round_trip(
+            &mut socket,
+            json!({ "id": 1, "method": "Runtime.evaluate", "params": { "expression": "6 * 7;" } }),
+        )
+        .await;
+        assert_eq!(response["result"]["result"]["value"], 42);
+    }
+
+    #[tokio::test]
+    async fn runtime_evaluate_separates_exceptions_from_broker_failures() {
+        let mut socket = connect().await;
+        let thrown = round_trip(
+            &mut socket,
+            json!({ "id": 1, "method": "Runtime.evaluate", "params": { "expression": "throw 1" } }),
+        )
+        .await;
+        assert!(thrown["result"]["exceptionDetails"]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("boom")));
+
+        let failed = round_trip(
+            &mut socket,
+            json!({ "id": 2, "method": "Runtime.evaluate", "params": { "expression": "wedged" } }),
+        )
+        .await;
+        assert_eq!(
+            failed,
+            json!({ "id": 2, "error": { "code": -32000, "message": "connection reset" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn closing_the_socket_closes_its_page() {
+        let (mut socket, mut closed) = connect_with_log().await;
+        round_trip(
+            &mut socket,
+            json!({ "id": 1, "method": "Runtime.evaluate", "params": { "expression": "6 * 7" } }),
+        )
+        .await;
+        socket.close(None).await.unwrap();
+        assert_eq!(closed.recv().await, Some(7));
+    }
+}
@@ -1,9 +1,23 @@
 use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
-#[command(name = "pneuma", version, about = "Headless browser orchestration runtime")]
+#[command(
+    name = "pneuma",
+    version,
+    about = "Headless browser orchestration runtime",
+    after_help = "Exit codes: 0 success, 1 other failure, 2 engine unavailable, 3 script error, 4 timeout."
+)]
 pub struct Args {
+    /// Config file (TOML, or JSON by extension). Defaults to `pneuma.toml` or
+    /// `pneuma.json` in the working directory.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Servo prefs file passed to a spawned Servo (e.g. to disable images or
+    /// set the DPI). Overrides the config file and `PNEUMA_SERVO_PREFS`.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub servo_prefs: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Command,
 }
@@ -12,27 +26,78 @@ pub struct Args {
 pub enum Command {
     Run {
         script: PathBuf,
-        #[arg(long, value_enum, default_value_t = EngineChoice::Servo)]
-        engine: EngineChoice,
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
         #[arg(long, default_value_t = false)]
         stealth: bool,
         #[arg(long)]
         profile: Option<PathBuf>,
+        /// Directory of `.js` files injected into every page, in file-name order.
+        #[arg(long, value_name = "DIR")]
+        init_scripts: Option<PathBuf>,
     },
     Eval {
         expression: String,
-        #[arg(long, value_enum, default_value_t = EngineChoice::Servo)]
-        engine: EngineChoice,
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
+        /// Directory of `.js` files injected into every page, in file-name order.
+        #[arg(long, value_name = "DIR")]
+        init_scripts: Option<PathBuf>,
+    },
+    /// Navigate to a URL and print the raw confidence signals and full report
+    /// as JSON, without escalation.
+    Probe {
+        url: String,
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
+    },
+    /// Re-score a JSONL file of recorded confidence signals with the scorer
+    /// from `--config`, printing one JSON decision per line. No engine runs.
+    Replay {
+        #[arg(value_name = "SIGNALS_FILE")]
+        signals_file: PathBuf,
+    },
+    /// Check the config file, the Servo binary or WebDriver endpoint and the
+    /// environment without running anything. Exits nonzero if a check fails.
+    Doctor {
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
+        /// Also start the engine, create a session and close it.
+        #[arg(long, default_value_t = false)]
+        session: bool,
+    },
+    /// Navigate every URL in a file (one per line, `#` comments allowed) and
+    /// print one JSON result per line.
+    Batch {
+        #[arg(value_name = "URLS_FILE")]
+        urls_file: PathBuf,
+        /// Record each completed URL here and skip those already recorded, so
+        /// an interrupted batch can be resumed by running it again.
+        #[arg(long, value_name = "FILE")]
+        checkpoint: Option<PathBuf>,
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
     },
     Serve {
         #[arg(long, default_value_t = 3000)]
         port: u16,
-        #[arg(long, value_enum, default_value_t = EngineChoice::Servo)]
-        engine: EngineChoice,
+        /// Also accept Chrome DevTools Protocol clients on this port; only a
+        /// small subset of CDP is understood (see `cdp.rs`).
+        #[arg(long, value_name = "PORT")]
+        cdp_port: Option<u16>,
+        /// Defaults to the config file's `engine.kind`, then Servo.
+        #[arg(long, value_enum)]
+        engine: Option<EngineChoice>,
     },
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EngineChoice {
     Servo,
     Ladybird,
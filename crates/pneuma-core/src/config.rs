@@ -0,0 +1,302 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use pneuma_broker::config::{BrokerConfig, ScorerConfig};
+use pneuma_broker::service::ServiceOptions;
+use pneuma_js::{EvalPolicy, RuntimeLimits, RuntimeOptions};
+use serde::Deserialize;
+
+use crate::cli::EngineChoice;
+
+/// Looked for in the working directory, in order, when `--config` is not given.
+const DEFAULT_CONFIG_FILES: &[&str] = &["pneuma.toml", "pneuma.json"];
+
+/// Runtime configuration from `pneuma.toml` / `pneuma.json` (or `--config`).
+/// Every setting is optional: CLI flags win over the file, and the file wins
+/// over environment variables. Relative paths resolve against the working
+/// directory.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PneumaConfig {
+    pub engine: EngineConfig,
+    pub broker: BrokerConfig,
+    pub scorer: ScorerConfig,
+    pub js: JsConfig,
+}
+
+/// `[engine]` section: how the primary engine is started.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineConfig {
+    /// Engine used when `--engine` is not given.
+    pub kind: Option<EngineChoice>,
+    /// Overrides `SERVO_WEBDRIVER_URL`.
+    pub webdriver_url: Option<String>,
+    /// Prefs file for a spawned Servo; overrides `PNEUMA_SERVO_PREFS`.
+    pub servo_prefs: Option<PathBuf>,
+}
+
+/// `[js]` section: limits on the QuickJS runtime scripts run in.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsConfig {
+    /// Heap limit in MiB; 256 when unset.
+    pub memory_limit_mb: Option<usize>,
+    /// Interpreter stack limit in KiB; 1024 when unset.
+    pub max_stack_kb: Option<usize>,
+    /// Hex SHA-256 of the only scripts pages may evaluate; unset allows any.
+    pub eval_allow_hashes: Option<Vec<String>>,
+    /// Scripts containing any of these (whitespace ignored) are refused.
+    pub eval_deny_patterns: Vec<String>,
+}
+
+impl JsConfig {
+    /// The configured limits over [`RuntimeLimits::default`]. Zero is
+    /// rejected: QuickJS would read it as "no limit".
+    pub fn limits(&self) -> Result<RuntimeLimits> {
+        let mut limits = RuntimeLimits::default();
+        if let Some(mb) = self.memory_limit_mb {
+            anyhow::ensure!(mb > 0, "js.memory_limit_mb must be greater than 0");
+            limits.memory_bytes = mb.saturating_mul(1024 * 1024);
+        }
+        if let Some(kb) = self.max_stack_kb {
+            anyhow::ensure!(kb > 0, "js.max_stack_kb must be greater than 0");
+            limits.max_stack_bytes = kb.saturating_mul(1024);
+        }
+        Ok(limits)
+    }
+
+    /// Options for the CLI's (unsandboxed) runtime.
+    pub fn runtime_options(&self) -> Result<RuntimeOptions> {
+        Ok(RuntimeOptions {
+            limits: self.limits()?,
+            eval_policy: self.eval_policy(),
+            ..RuntimeOptions::default()
+        })
+    }
+
+    pub fn eval_policy(&self) -> EvalPolicy {
+        let mut policy = EvalPolicy::default();
+        if let Some(hashes) = &self.eval_allow_hashes {
+            policy = policy.allow_hashes(hashes);
+        }
+        self.eval_deny_patterns
+            .iter()
+            .fold(policy, |policy, pattern| policy.deny_pattern(pattern))
+    }
+}
+
+impl PneumaConfig {
+    /// Load `path`, or else the first default file present in the working
+    /// directory. Defaults when there is neither.
+    pub fn discover(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::load(path);
+        }
+        match DEFAULT_CONFIG_FILES.iter().map(Path::new).find(|path| path.is_file()) {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Parse `path` as JSON when it ends in `.json`, TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("failed to read config file {}", path.display()))?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+        .with_context(|| format!("invalid config file {}", path.display()))?;
+        tracing::info!(path = %path.display(), "loaded config file");
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text)?;
+        config.validate()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let config: Self = serde_json::from_str(text)?;
+        config.validate()
+    }
+
+    fn validate(self) -> Result<Self> {
+        self.scorer.scorer()?;
+        self.js.limits()?;
+        Ok(self)
+    }
+
+    /// `--engine` when given, else the configured kind, else Servo.
+    pub fn engine(&self, cli: Option<EngineChoice>) -> EngineChoice {
+        cli.or(self.engine.kind).unwrap_or(EngineChoice::Servo)
+    }
+
+    /// This config with `--servo-prefs`, when given, in place of the file's.
+    pub fn with_servo_prefs(mut self, cli: Option<PathBuf>) -> Self {
+        if cli.is_some() {
+            self.engine.servo_prefs = cli;
+        }
+        self
+    }
+
+    /// `--init-scripts` when given, else the configured directory.
+    pub fn init_scripts(&self, cli: Option<PathBuf>) -> Option<PathBuf> {
+        cli.or_else(|| self.broker.init_scripts.clone())
+    }
+
+    /// Service options from the environment with the file's settings on top.
+    pub fn service_options(&self) -> Result<ServiceOptions> {
+        let mut options = ServiceOptions::from_env();
+        self.broker.apply(&mut options);
+        options.decision_band = self.scorer.decision_band()?;
+        options.paint_curve = self.scorer.paint_curve()?;
+        options.http_errors = self.scorer.http_errors.unwrap_or_default();
+        options.dom_interactive_threshold_ms = self.scorer.dom_interactive_threshold_ms()?;
+        options.ttfb_penalty = self.scorer.ttfb_penalty.unwrap_or(false);
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PneumaConfig;
+    use crate::cli::EngineChoice;
+    use std::path::PathBuf;
+
+    const FULL_TOML: &str = r#"
+[engine]
+kind = "ladybird"
+webdriver_url = "http://127.0.0.1:7000"
+servo_prefs = "servo/prefs.json"
+
+[broker]
+max_escalations = 2
+state_dir = "/var/lib/pneuma"
+diag_dir = "/tmp/pneuma-diag"
+init_scripts = "scripts/init"
+stamp_migrated = false
+migrated_key = "pneuma_migrated"
+secondary_webdriver_urls = ["http://127.0.0.1:7001", "http://127.0.0.1:7002"]
+coalesce_navigates = true
+empty_state_handoff = "abort"
+strict_escalation = true
+engine_timeout_ms = 20000
+
+[js]
+memory_limit_mb = 64
+max_stack_kb = 512
+eval_deny_patterns = ["while(true)"]
+
+[scorer]
+escalate_below = 0.4
+stay_at = 0.7
+dom_interactive_threshold_ms = 15000
+ttfb_penalty = true
+
+[scorer.paint]
+curve = "logistic"
+midpoint_ms = 6000
+"#;
+
+    fn full_config() -> PneumaConfig {
+        PneumaConfig::from_toml(FULL_TOML).expect("full TOML config should parse")
+    }
+
+    #[test]
+    fn parses_the_engine_section() {
+        let config = full_config();
+        assert_eq!(config.engine.kind, Some(EngineChoice::Ladybird));
+        assert_eq!(config.engine.webdriver_url.as_deref(), Some("http://127.0.0.1:7000"));
+        assert_eq!(config.engine.servo_prefs, Some(PathBuf::from("servo/prefs.json")));
+        let cli_prefs = config.clone().with_servo_prefs(Some(PathBuf::from("cli.json")));
+        assert_eq!(cli_prefs.engine.servo_prefs, Some(PathBuf::from("cli.json")));
+        assert_eq!(config.clone().with_servo_prefs(None), config);
+    }
+
+    #[test]
+    fn parses_the_broker_section() {
+        let config = full_config();
+        assert_eq!(config.broker.max_escalations, Some(2));
+        assert_eq!(config.broker.state_dir, Some(PathBuf::from("/var/lib/pneuma")));
+        assert_eq!(config.broker.init_scripts, Some(PathBuf::from("scripts/init")));
+        assert_eq!(config.broker.stamp_migrated, Some(false));
+        assert_eq!(config.broker.migrated_key.as_deref(), Some("pneuma_migrated"));
+        assert_eq!(config.broker.secondary_webdriver_urls.as_ref().map(Vec::len), Some(2));
+
+        let options = config.service_options().unwrap();
+        assert_eq!(options.max_escalations, 2);
+        assert!(!options.stamp_migrated);
+        assert_eq!(options.migrated_key, "pneuma_migrated");
+        assert!(options.coalesce_navigates);
+        assert_eq!(options.empty_state_handoff, pneuma_broker::service::EmptyStateHandoff::Abort);
+        assert!(options.strict_escalation);
+        let timeouts = options.engine_timeouts.expect("an engine timeout turns the limits on");
+        assert_eq!(timeouts.evaluate, std::time::Duration::from_secs(20));
+        assert_eq!(timeouts.navigate, pneuma_engines::EngineTimeouts::default().navigate);
+        assert_eq!(options.diagnostics.unwrap().dir(), std::path::Path::new("/tmp/pneuma-diag"));
+    }
+
+    #[test]
+    fn parses_the_js_section() {
+        let config = full_config();
+        let limits = config.js.limits().unwrap();
+        assert_eq!((limits.memory_bytes, limits.max_stack_bytes), (64 * 1024 * 1024, 512 * 1024));
+        assert!(config.js.eval_policy().check("while (true) {}").is_err());
+        assert!(PneumaConfig::default().js.eval_policy().is_allow_all());
+        assert!(PneumaConfig::from_toml("[js]\nmemory_limit_mb = 0").is_err(), "a zero limit would mean none");
+    }
+
+    #[test]
+    fn parses_the_scorer_section() {
+        let config = full_config();
+        let band = config.scorer.decision_band().unwrap();
+        assert_eq!((band.escalate_below, band.stay_at), (0.4, 0.7));
+
+        let options = config.service_options().unwrap();
+        assert_eq!(options.decision_band, band);
+        assert_eq!(options.dom_interactive_threshold_ms, 15000);
+        assert!(options.ttfb_penalty);
+        assert_eq!(
+            options.paint_curve,
+            pneuma_broker::confidence::PaintCurve::Logistic {
+                midpoint_ms: 6000,
+                steepness_ms: 1000,
+                full_element_count: 100,
+            }
+        );
+        assert!(PneumaConfig::from_toml("[scorer]\nescalate_below = 0.8\nstay_at = 0.6").is_err());
+        assert!(PneumaConfig::from_toml("[scorer.paint]\nslow_ms = 9000").is_err(), "slow_ms above very_slow_ms");
+    }
+
+    #[test]
+    fn parses_json_configs() {
+        let json = r#"{"engine": {"kind": "servo"}, "broker": {"max_escalations": 0}, "scorer": {"stay_at": 0.9}}"#;
+        let config = PneumaConfig::from_json(json).expect("JSON config should parse");
+        assert_eq!(config.engine.kind, Some(EngineChoice::Servo));
+        assert_eq!(config.broker.max_escalations, Some(0));
+        assert_eq!(config.scorer.stay_at, Some(0.9));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(PneumaConfig::from_toml("[broker]\nmax_escalation = 2").is_err());
+        // Nothing selects a stealth profile yet, so the key is not accepted.
+        assert!(PneumaConfig::from_toml("stealth_profile = \"chrome_120\"").is_err());
+    }
+
+    #[test]
+    fn cli_flags_take_precedence_over_config() {
+        let config = full_config();
+        assert_eq!(config.engine(Some(EngineChoice::Servo)), EngineChoice::Servo);
+        assert_eq!(config.engine(None), EngineChoice::Ladybird);
+        assert_eq!(PneumaConfig::default().engine(None), EngineChoice::Servo);
+
+        let cli_dir = PathBuf::from("cli/init");
+        assert_eq!(config.init_scripts(Some(cli_dir.clone())), Some(cli_dir));
+        assert_eq!(config.init_scripts(None), Some(PathBuf::from("scripts/init")));
+        assert_eq!(PneumaConfig::default().init_scripts(None), None);
+    }
+}
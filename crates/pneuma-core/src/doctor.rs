@@ -0,0 +1,194 @@
+//! `pneuma doctor`: check the configuration and environment a run would use,
+//! without running a script, and print a pass/warn/fail checklist.
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use pneuma_engines::servo;
+
+use crate::cli::EngineChoice;
+use crate::config::PneumaConfig;
+use crate::exit;
+
+/// Variables the engines and broker read, reported when set.
+const ENV_VARS: &[&str] = &[
+    "SERVO_WEBDRIVER_URL",
+    "SERVO_BIN",
+    "SERVO_SECONDARY_WEBDRIVER_URL",
+    "SERVO_SECONDARY_WEBDRIVER_URLS",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Worth a look, but a run can still work.
+    Warn,
+    /// A run would fail; makes `doctor` exit nonzero.
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        write!(f, "{label}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    /// `[status] name: detail`, with any further detail lines indented.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = self.detail.lines();
+        write!(f, "[{}] {}: {}", self.status, self.name, lines.next().unwrap_or_default())?;
+        lines.try_for_each(|line| write!(f, "\n       {line}"))
+    }
+}
+
+/// Run every check, print the checklist, and fail when any check failed.
+/// `config` is the result of loading the config file, so a broken file is
+/// reported rather than stopping the other checks.
+pub async fn doctor(
+    config: Result<PneumaConfig>,
+    config_path: Option<&Path>,
+    engine: Option<EngineChoice>,
+    session: bool,
+) -> Result<()> {
+    let checks = run_checks(config, config_path, engine, session).await;
+    for check in &checks {
+        println!("{check}");
+    }
+    let failed = checks.iter().filter(|check| check.status == Status::Fail).count();
+    if failed > 0 {
+        bail!("{failed} of {} doctor checks failed", checks.len());
+    }
+    Ok(())
+}
+
+async fn run_checks(
+    config: Result<PneumaConfig>,
+    config_path: Option<&Path>,
+    engine: Option<EngineChoice>,
+    session: bool,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let config = match config.and_then(validate_settings) {
+        Ok(config) => {
+            let detail = match config_path {
+                Some(path) => format!("{} is valid", path.display()),
+                None => "valid (a default file if present, else built-in defaults)".to_string(),
+            };
+            checks.push(Check::new("config", Status::Pass, detail));
+            config
+        }
+        Err(error) => {
+            checks.push(Check::new("config", Status::Fail, format!("{error:#}")));
+            PneumaConfig::default()
+        }
+    };
+
+    let engine = config.engine(engine);
+    if engine == EngineChoice::Ladybird {
+        checks.push(Check::new("engine", Status::Fail, "ladybird engine is not wired yet"));
+        return checks;
+    }
+    checks.push(Check::new("engine", Status::Pass, "servo"));
+    checks.push(environment_check(&config));
+
+    // Same precedence as launching: the config file's endpoint, then the variable.
+    let endpoint = config
+        .engine
+        .webdriver_url
+        .clone()
+        .or_else(|| std::env::var("SERVO_WEBDRIVER_URL").ok());
+    let engine_ready = match endpoint {
+        Some(endpoint) => match servo::check_endpoint(endpoint).await {
+            Ok(base_url) => Check::new("webdriver endpoint", Status::Pass, format!("{base_url} is ready")),
+            Err(error) => Check::new("webdriver endpoint", Status::Fail, describe(&error)),
+        },
+        None => match servo::resolve_servo_binary() {
+            Ok(path) => Check::new("servo binary", Status::Pass, path.display().to_string()),
+            Err(error) => Check::new("servo binary", Status::Fail, describe(&error)),
+        },
+    };
+    let can_launch = engine_ready.status == Status::Pass;
+    checks.push(engine_ready);
+
+    if session {
+        checks.push(if can_launch {
+            session_check(engine, &config).await
+        } else {
+            Check::new("session", Status::Fail, "skipped: the engine cannot start")
+        });
+    }
+    checks
+}
+
+/// Settings only checked when a run builds its options from the file.
+fn validate_settings(config: PneumaConfig) -> Result<PneumaConfig> {
+    config.service_options()?;
+    config.js.runtime_options()?;
+    Ok(config)
+}
+
+fn environment_check(config: &PneumaConfig) -> Check {
+    let set: Vec<&str> = ENV_VARS
+        .iter()
+        .copied()
+        .filter(|name| std::env::var_os(name).is_some())
+        .collect();
+    if set.is_empty() && config.engine.webdriver_url.is_none() {
+        return Check::new(
+            "environment",
+            Status::Warn,
+            "neither SERVO_WEBDRIVER_URL nor SERVO_BIN is set; looking for `servo` on PATH",
+        );
+    }
+    let detail = if set.is_empty() {
+        "endpoint from the config file".to_string()
+    } else {
+        format!("set: {}", set.join(", "))
+    };
+    Check::new("environment", Status::Pass, detail)
+}
+
+/// Start the engine as a run would, then close it again.
+async fn session_check(engine: EngineChoice, config: &PneumaConfig) -> Check {
+    let engine = match crate::launch_engine(engine, config).await {
+        Ok(engine) => engine,
+        Err(error) => return Check::new("session", Status::Fail, describe(&error)),
+    };
+    let instance = engine.instance_id().to_string();
+    match engine.close().await {
+        Ok(()) => Check::new("session", Status::Pass, format!("created and closed session on {instance}")),
+        Err(error) => Check::new(
+            "session",
+            Status::Warn,
+            format!("session on {instance} was created but failed to close: {error:#}"),
+        ),
+    }
+}
+
+fn describe(error: &anyhow::Error) -> String {
+    exit::friendly_message(error).unwrap_or_else(|| format!("{error:#}"))
+}
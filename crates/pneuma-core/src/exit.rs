@@ -0,0 +1,112 @@
+//! Process exit codes reported by the `pneuma` binary.
+//!
+//! | Code | Meaning                                                        |
+//! |------|----------------------------------------------------------------|
+//! | 0    | Success                                                        |
+//! | 1    | Any other failure                                              |
+//! | 2    | Engine unavailable (Servo failed to start, no WebDriver)       |
+//! | 3    | Script error (the script or expression threw)                  |
+//! | 4    | Timeout waiting on the engine                                  |
+//! | 5    | Servo binary not found (install Servo or set `SERVO_BIN`)      |
+//!
+//! A script calling `ghost.exit(code)` exits with that code directly.
+
+use std::fmt;
+use std::process::ExitCode;
+
+use pneuma_engines::EngineError;
+
+pub const FAILURE: u8 = 1;
+pub const ENGINE_UNAVAILABLE: u8 = 2;
+pub const SCRIPT_ERROR: u8 = 3;
+pub const TIMEOUT: u8 = 4;
+pub const BINARY_NOT_FOUND: u8 = 5;
+
+/// Context marker attached to failures raised while running user JS.
+#[derive(Debug)]
+pub struct ScriptFailed;
+
+impl fmt::Display for ScriptFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "script failed")
+    }
+}
+
+fn engine_error(error: &anyhow::Error) -> Option<&EngineError> {
+    error
+        .downcast_ref::<EngineError>()
+        .or_else(|| error.chain().find_map(|cause| cause.downcast_ref::<EngineError>()))
+}
+
+pub fn code_for(error: &anyhow::Error) -> u8 {
+    match engine_error(error) {
+        Some(EngineError::Unavailable(_)) => return ENGINE_UNAVAILABLE,
+        Some(EngineError::BinaryNotFound { .. }) => return BINARY_NOT_FOUND,
+        Some(EngineError::Timeout(_)) => return TIMEOUT,
+        _ => {}
+    }
+    if error.downcast_ref::<ScriptFailed>().is_some() {
+        return SCRIPT_ERROR;
+    }
+    FAILURE
+}
+
+pub fn exit_code_for(error: &anyhow::Error) -> ExitCode {
+    ExitCode::from(code_for(error))
+}
+
+/// What to print instead of the raw error chain for failures a first-time
+/// user is expected to hit, or `None` to print the chain.
+pub fn friendly_message(error: &anyhow::Error) -> Option<String> {
+    match engine_error(error)? {
+        EngineError::BinaryNotFound { searched } => {
+            let mut message = String::from("Servo was not found. Looked in:\n");
+            for path in searched {
+                message.push_str(&format!("  {}\n", path.display()));
+            }
+            message.push_str(
+                "Install Servo (https://servo.org/download/) and put `servo` on PATH, \
+                 or set SERVO_BIN to the Servo executable.\n\
+                 To use a Servo that is already running, set SERVO_WEBDRIVER_URL to its WebDriver endpoint.",
+            );
+            Some(message)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn classifies_engine_and_script_failures() {
+        let unavailable = anyhow::Error::from(EngineError::Unavailable("servo missing".into()));
+        assert_eq!(code_for(&unavailable), ENGINE_UNAVAILABLE);
+
+        let timeout = anyhow::Error::from(EngineError::Timeout("title never ready".into()))
+            .context("navigate failed");
+        assert_eq!(code_for(&timeout), TIMEOUT);
+
+        let script: anyhow::Result<()> = Err(anyhow::anyhow!("ReferenceError: x is not defined"));
+        let script = script.context(ScriptFailed).unwrap_err();
+        assert_eq!(code_for(&script), SCRIPT_ERROR);
+
+        assert_eq!(code_for(&anyhow::anyhow!("something else")), FAILURE);
+    }
+
+    #[test]
+    fn missing_binary_gets_its_own_code_and_install_hint() {
+        let missing = anyhow::Error::from(EngineError::BinaryNotFound {
+            searched: vec!["/opt/servo/servo".into(), "/usr/bin/servo".into()],
+        })
+        .context("failed to start primary engine");
+        assert_eq!(code_for(&missing), BINARY_NOT_FOUND);
+
+        let message = friendly_message(&missing).expect("missing binary has a friendly message");
+        assert!(message.contains("  /opt/servo/servo\n  /usr/bin/servo\n"), "{message}");
+        assert!(message.contains("SERVO_BIN"), "{message}");
+        assert!(friendly_message(&anyhow::anyhow!("something else")).is_none());
+    }
+}
@@ -1,12 +1,23 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use pneuma_engines::servo::ServoEngine;
+use pneuma_engines::EngineError;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
+mod batch;
+mod cdp;
 mod cli;
+mod config;
+mod doctor;
+mod exit;
+mod replay;
+mod serve;
 use cli::Args;
+use config::PneumaConfig;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     tracing_subscriber::fmt()
         .with_env_filter(std::env::var("PNEUMA_LOG").unwrap_or_else(|_| "pneuma=info".into()))
         .init();
@@ -15,36 +26,122 @@ async fn main() -> Result<()> {
 
     tracing::info!(version = env!("CARGO_PKG_VERSION"), "Pneuma starting");
 
-    match args.command {
+    let config = PneumaConfig::discover(args.config.as_deref()).map(|config| config.with_servo_prefs(args.servo_prefs));
+    let result = match (args.command, config) {
+        // Doctor reports a broken config file as one of its checks.
+        (cli::Command::Doctor { engine, session }, config) => {
+            doctor::doctor(config, args.config.as_deref(), engine, session).await
+        }
+        (command, Ok(config)) => run_command(command, &config).await,
+        (_, Err(error)) => Err(error),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            match exit::friendly_message(&error) {
+                Some(message) => eprintln!("Error: {message}"),
+                None => eprintln!("Error: {error:?}"),
+            }
+            exit::exit_code_for(&error)
+        }
+    }
+}
+
+async fn run_command(command: cli::Command, config: &PneumaConfig) -> Result<()> {
+    match command {
         cli::Command::Run {
             script,
             engine,
             stealth,
+            init_scripts,
             ..
-        } => run_script(script, engine, stealth).await,
-        cli::Command::Eval { expression, engine } => eval_expression(expression, engine).await,
-        cli::Command::Serve { port, .. } => serve(port).await,
+        } => {
+            let engine = config.engine(engine);
+            run_script(script, engine, stealth, config.init_scripts(init_scripts), config).await
+        }
+        cli::Command::Eval {
+            expression,
+            engine,
+            init_scripts,
+        } => eval_expression(expression, config.engine(engine), config.init_scripts(init_scripts), config).await,
+        cli::Command::Probe { url, engine } => probe(url, config.engine(engine), config).await,
+        cli::Command::Replay { signals_file } => replay_signals(&signals_file, config),
+        cli::Command::Batch {
+            urls_file,
+            checkpoint,
+            engine,
+        } => run_batch(&urls_file, checkpoint.as_deref(), config.engine(engine), config).await,
+        cli::Command::Serve { port, cdp_port, engine } => serve(port, cdp_port, config.engine(engine), config).await,
+        cli::Command::Doctor { .. } => unreachable!("doctor runs before the config is required"),
+    }
+}
+
+async fn launch_engine(
+    engine: cli::EngineChoice,
+    config: &PneumaConfig,
+) -> Result<Box<dyn pneuma_engines::HeadlessEngine>> {
+    match engine {
+        cli::EngineChoice::Servo => match (&config.engine.webdriver_url, &config.engine.servo_prefs) {
+            (Some(url), _) => Ok(Box::new(ServoEngine::launch_with_endpoint(url.clone()).await?)),
+            (None, Some(prefs)) => Ok(Box::new(ServoEngine::launch_with_prefs(Some(prefs.clone())).await?)),
+            (None, None) => Ok(Box::new(ServoEngine::launch().await?)),
+        },
+        cli::EngineChoice::Ladybird => {
+            Err(EngineError::Unavailable("ladybird engine is not wired yet".into()).into())
+        }
     }
 }
 
-async fn spawn_broker_handle(engine: cli::EngineChoice) -> Result<pneuma_broker::handle::BrokerHandle> {
-    let runtime_engine: Box<dyn pneuma_engines::HeadlessEngine> = match engine {
-        cli::EngineChoice::Servo => Box::new(ServoEngine::launch().await?),
-        cli::EngineChoice::Ladybird => anyhow::bail!("ladybird engine is not wired yet"),
+async fn spawn_broker_handle(
+    engine: cli::EngineChoice,
+    init_scripts: Option<PathBuf>,
+    config: &PneumaConfig,
+) -> Result<pneuma_broker::handle::BrokerHandle> {
+    let init_scripts = match init_scripts {
+        Some(dir) => pneuma_broker::init_scripts::load_init_scripts(&dir)?,
+        None => Vec::new(),
     };
+    let options = pneuma_broker::service::ServiceOptions {
+        init_scripts,
+        ..config.service_options()?
+    };
+    spawn_broker(engine, options, config).await
+}
+
+async fn spawn_broker(
+    engine: cli::EngineChoice,
+    options: pneuma_broker::service::ServiceOptions,
+    config: &PneumaConfig,
+) -> Result<pneuma_broker::handle::BrokerHandle> {
+    let runtime_engine = launch_engine(engine, config).await?;
 
     let (broker_tx, broker_rx) = tokio::sync::mpsc::unbounded_channel();
     let handle = pneuma_broker::handle::BrokerHandle::new(broker_tx);
-    tokio::spawn(pneuma_broker::service::run(broker_rx, runtime_engine));
+    tokio::spawn(pneuma_broker::service::run_with_options(
+        broker_rx,
+        runtime_engine,
+        config
+            .broker
+            .escalation_factory()
+            .with_servo_prefs(config.engine.servo_prefs.clone()),
+        options,
+    ));
     Ok(handle)
 }
 
-async fn run_script(script: std::path::PathBuf, engine: cli::EngineChoice, stealth: bool) -> Result<()> {
+async fn run_script(
+    script: PathBuf,
+    engine: cli::EngineChoice,
+    stealth: bool,
+    init_scripts: Option<PathBuf>,
+    config: &PneumaConfig,
+) -> Result<()> {
     let source = std::fs::read_to_string(&script)?;
 
-    let handle = spawn_broker_handle(engine).await?;
-    let runtime = pneuma_js::Runtime::new(handle)?;
-    runtime.execute_script(&source)?;
+    let handle = spawn_broker_handle(engine, init_scripts, config).await?;
+    let runtime = pneuma_js::Runtime::with_options(handle, config.js.runtime_options()?)?;
+    runtime.execute_script(&source).context(exit::ScriptFailed)?;
 
     // TODO(week-9): replace direct CLI engine selection with confidence-based routing.
     tracing::info!(
@@ -58,17 +155,108 @@ async fn run_script(script: std::path::PathBuf, engine: cli::EngineChoice, steal
     Ok(())
 }
 
-async fn eval_expression(expr: String, engine: cli::EngineChoice) -> Result<()> {
+async fn eval_expression(
+    expr: String,
+    engine: cli::EngineChoice,
+    init_scripts: Option<PathBuf>,
+    config: &PneumaConfig,
+) -> Result<()> {
     tracing::info!("evaluating expression");
-    let handle = spawn_broker_handle(engine).await?;
-    let runtime = pneuma_js::Runtime::new(handle)?;
-    let rendered = runtime.eval_expression(&expr)?;
+    let handle = spawn_broker_handle(engine, init_scripts, config).await?;
+    let runtime = pneuma_js::Runtime::with_options(handle, config.js.runtime_options()?)?;
+    let rendered = runtime.eval_expression(&expr).context(exit::ScriptFailed)?;
     println!("{rendered}");
     Ok(())
 }
 
-async fn serve(port: u16) -> Result<()> {
-    tracing::info!(port, "starting server mode");
-    println!("serve on :{}", port);
+async fn probe(url: String, engine: cli::EngineChoice, config: &PneumaConfig) -> Result<()> {
+    tracing::info!(url = %url, ?engine, "probing confidence");
+    let runtime_engine = launch_engine(engine, config).await?;
+    let navigated = runtime_engine.navigate(&url, "{}").await;
+    if let Err(error) = runtime_engine.close().await {
+        tracing::warn!(error = %error, "engine close after probe failed");
+    }
+    let meta_json = navigated?;
+
+    let signals = pneuma_broker::confidence::signals_from_navigate_meta(&meta_json, 0);
+    let report = config.scorer.scorer()?.score(&signals);
+    let meta: serde_json::Value =
+        serde_json::from_str(&meta_json).unwrap_or(serde_json::Value::String(meta_json));
+    let output = serde_json::json!({
+        "url": url,
+        "engine": runtime_engine.name(),
+        "meta": meta,
+        "signals": signals,
+        "report": report,
+        "explanation": report.explain(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
+
+fn replay_signals(signals_file: &std::path::Path, config: &PneumaConfig) -> Result<()> {
+    let file = std::fs::File::open(signals_file)
+        .with_context(|| format!("failed to open signals file {}", signals_file.display()))?;
+    let scorer = config.scorer.scorer()?;
+    let scored = replay::replay(std::io::BufReader::new(file), &scorer, std::io::stdout().lock())?;
+    tracing::info!(path = %signals_file.display(), scored, "replayed recorded signals");
+    Ok(())
+}
+
+async fn run_batch(
+    urls_file: &std::path::Path,
+    checkpoint: Option<&std::path::Path>,
+    engine: cli::EngineChoice,
+    config: &PneumaConfig,
+) -> Result<()> {
+    let file = std::fs::File::open(urls_file)
+        .with_context(|| format!("failed to open URL list {}", urls_file.display()))?;
+    let urls = batch::read_urls(std::io::BufReader::new(file))?;
+    let checkpoint = checkpoint.map(batch::Checkpoint::open).transpose()?;
+
+    // Through the broker, so the batch gets escalation, policy and timeouts.
+    let handle = spawn_broker_handle(engine, None, config).await?;
+    let page_id = {
+        let handle = handle.clone();
+        tokio::task::spawn_blocking(move || handle.create_page()).await??
+    };
+    let navigate = |url: String| {
+        let handle = handle.clone();
+        async move { Ok(tokio::task::spawn_blocking(move || handle.navigate(page_id, url, "{}".into())).await??) }
+    };
+    let summary = batch::run_batch(&urls, checkpoint, navigate, std::io::stdout().lock()).await;
+    if let Err(error) = tokio::task::spawn_blocking(move || handle.shutdown()).await? {
+        tracing::warn!(error = %error, "broker shutdown after batch failed");
+    }
+    let summary = summary?;
+    tracing::info!(
+        navigated = summary.navigated,
+        skipped = summary.skipped,
+        failed = summary.failed,
+        "batch finished"
+    );
+    if summary.failed > 0 {
+        anyhow::bail!("{} of {} URLs failed", summary.failed, urls.len());
+    }
+    Ok(())
+}
+
+async fn serve(port: u16, cdp_port: Option<u16>, engine: cli::EngineChoice, config: &PneumaConfig) -> Result<()> {
+    tracing::info!(port, ?cdp_port, "starting server mode");
+    let options = config.service_options()?;
+    let metrics = options.metrics.clone();
+    // Held for the life of the server so the service loop keeps running.
+    let handle = spawn_broker(engine, options, config).await?;
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("failed to bind 127.0.0.1:{port}"))?;
+    if let Some(cdp_port) = cdp_port {
+        let cdp_listener = tokio::net::TcpListener::bind(("127.0.0.1", cdp_port))
+            .await
+            .with_context(|| format!("failed to bind 127.0.0.1:{cdp_port}"))?;
+        println!("cdp on :{cdp_port}");
+        tokio::spawn(cdp::serve_cdp(cdp_listener, handle.clone()));
+    }
+    println!("serve on :{port}");
+    serve::serve_api(listener, metrics, handle).await
+}
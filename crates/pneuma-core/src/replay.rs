@@ -0,0 +1,43 @@
+//! `pneuma replay`: re-score recorded confidence signals offline, so scorer
+//! settings can be tuned against real pages without launching an engine.
+
+use std::io::{BufRead, Write};
+
+use anyhow::{Context, Result};
+use pneuma_broker::confidence::{ConfidenceScorer, ConfidenceSignals};
+use serde_json::Value;
+
+/// Score every non-blank line of `input` with `scorer` and write one JSON
+/// line per input line to `out`. A line is either a bare
+/// [`ConfidenceSignals`] object or a record carrying one under `signals`
+/// (as `pneuma probe` prints). Lines are scored independently, without the
+/// hysteresis a live page gets. Returns how many lines were scored.
+pub fn replay(input: impl BufRead, scorer: &ConfidenceScorer, mut out: impl Write) -> Result<usize> {
+    let mut scored = 0;
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.with_context(|| format!("failed to read line {line_number}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let signals = parse_signals(&line).with_context(|| format!("invalid signals on line {line_number}"))?;
+        let report = scorer.score(&signals);
+        let output = serde_json::json!({
+            "line": line_number,
+            "decision": report.decision,
+            "explanation": report.explain(),
+            "report": report,
+        });
+        writeln!(out, "{output}")?;
+        scored += 1;
+    }
+    Ok(scored)
+}
+
+fn parse_signals(line: &str) -> Result<ConfidenceSignals> {
+    let mut value: Value = serde_json::from_str(line)?;
+    if let Some(signals) = value.get_mut("signals") {
+        return Ok(serde_json::from_value(signals.take())?);
+    }
+    Ok(serde_json::from_value(value)?)
+}
@@ -0,0 +1,213 @@
+//! Minimal HTTP endpoint for serve mode: `GET /metrics`, `POST /rollback` to
+//! put the standby primary back in charge after a spurious escalation, and
+//! `POST /reset` to replace a wedged engine with a fresh primary.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use pneuma_broker::handle::BrokerHandle;
+use pneuma_broker::metrics::BrokerMetrics;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Longest request or header line read; a longer one is refused with 431.
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+
+/// Most header lines read; more are refused with 431, so a client cannot hold
+/// a connection (and, for `POST /rollback`, the broker call) open forever.
+const MAX_HEADER_LINES: usize = 100;
+
+/// Accept connections until the listener fails.
+pub async fn serve_api(listener: TcpListener, metrics: Arc<BrokerMetrics>, handle: BrokerHandle) -> Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await.context("failed to accept connection")?;
+        let metrics = metrics.clone();
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            if let Err(error) = respond(stream, &metrics, handle).await {
+                tracing::debug!(%peer, error = %error, "serve request failed");
+            }
+        });
+    }
+}
+
+/// Read one line of at most [`MAX_LINE_BYTES`]; `None` when it is longer.
+async fn read_capped_line(reader: &mut BufReader<TcpStream>) -> Result<Option<String>> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(MAX_LINE_BYTES).read_line(&mut line).await?;
+    if read as u64 == MAX_LINE_BYTES && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+/// The request line, after draining the headers; requests carry no body we
+/// care about. `None` when a line is too long or there are too many headers.
+async fn read_head(reader: &mut BufReader<TcpStream>) -> Result<Option<String>> {
+    let Some(request_line) = read_capped_line(reader).await? else {
+        return Ok(None);
+    };
+    for _ in 0..=MAX_HEADER_LINES {
+        match read_capped_line(reader).await? {
+            Some(header) if !header.trim().is_empty() => {}
+            Some(_) => return Ok(Some(request_line)),
+            None => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+async fn respond(stream: TcpStream, metrics: &BrokerMetrics, handle: BrokerHandle) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let (status, content_type, body) = match read_head(&mut reader).await? {
+        Some(request_line) => route(&request_line, metrics, handle).await?,
+        None => (
+            "431 Request Header Fields Too Large",
+            "text/plain; charset=utf-8",
+            "request line or header too long\n".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Status, content type and body for `request_line`.
+async fn route(
+    request_line: &str,
+    metrics: &BrokerMetrics,
+    handle: BrokerHandle,
+) -> Result<(&'static str, &'static str, String)> {
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.render_prometheus(),
+        ),
+        (Some("POST"), Some("/rollback")) => {
+            // Handle calls block until the service loop replies.
+            match tokio::task::spawn_blocking(move || handle.rollback()).await? {
+                Ok(rolled_back) => (
+                    "200 OK",
+                    "application/json",
+                    format!("{}\n", serde_json::json!({ "rolled_back": rolled_back })),
+                ),
+                Err(error) => (
+                    "500 Internal Server Error",
+                    "text/plain; charset=utf-8",
+                    format!("rollback failed: {error:#}\n"),
+                ),
+            }
+        }
+        (Some("POST"), Some("/reset")) => match tokio::task::spawn_blocking(move || handle.reset_engine()).await? {
+            Ok(()) => (
+                "200 OK",
+                "application/json",
+                format!("{}\n", serde_json::json!({ "reset": true })),
+            ),
+            Err(error) => (
+                "500 Internal Server Error",
+                "text/plain; charset=utf-8",
+                format!("reset failed: {error:#}\n"),
+            ),
+        },
+        _ => ("404 Not Found", "text/plain; charset=utf-8", "not found\n".to_string()),
+    };
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Requests = tokio::sync::mpsc::UnboundedReceiver<pneuma_broker::handle::BrokerRequest>;
+
+    /// Serve on a free port; the address and what reaches the broker.
+    async fn start() -> (std::net::SocketAddr, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind serve listener");
+        let addr = listener.local_addr().expect("serve addr");
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(serve_api(listener, Arc::default(), BrokerHandle::new(tx)));
+        (addr, rx)
+    }
+
+    /// Send `request` and read the whole response.
+    async fn send(addr: std::net::SocketAddr, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(addr).await.expect("connect");
+        stream.write_all(request).await.expect("write request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.expect("read response");
+        response
+    }
+
+    #[tokio::test]
+    async fn an_overlong_request_line_is_refused() {
+        let (addr, _requests) = start().await;
+        // Exactly the cap and no line end: all of it is read before refusing.
+        let request = vec![b'a'; MAX_LINE_BYTES as usize];
+        let response = send(addr, &request).await;
+        assert!(response.starts_with("HTTP/1.1 431 "), "{response}");
+
+        let response = send(addr, b"GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn rollback_is_not_requested_for_an_oversized_head() {
+        let (addr, mut requests) = start().await;
+        let mut request = b"POST /rollback HTTP/1.1\r\n".to_vec();
+        request.extend(vec![b'x'; MAX_LINE_BYTES as usize]);
+        let response = send(addr, &request).await;
+        assert!(response.starts_with("HTTP/1.1 431 "), "{response}");
+
+        let mut request = b"POST /rollback HTTP/1.1\r\n".to_vec();
+        // One header too many; the head is refused before its end.
+        for _ in 0..=MAX_HEADER_LINES {
+            request.extend(b"X-Filler: 1\r\n");
+        }
+        let response = send(addr, &request).await;
+        assert!(response.starts_with("HTTP/1.1 431 "), "{response}");
+        assert!(requests.try_recv().is_err(), "the broker is never asked to roll back");
+
+        let broker = tokio::spawn(async move {
+            match requests.recv().await {
+                Some(pneuma_broker::handle::BrokerRequest::Rollback { reply }) => drop(reply.send(Ok(true))),
+                _ => panic!("expected a rollback request"),
+            }
+        });
+        let response = send(addr, b"POST /rollback HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.contains(r#""rolled_back":true"#), "{response}");
+        broker.await.expect("broker task");
+    }
+
+    #[tokio::test]
+    async fn reset_replaces_the_engine_through_the_broker() {
+        let (addr, mut requests) = start().await;
+        let broker = tokio::spawn(async move {
+            for outcome in [Ok(()), Err(anyhow::anyhow!("factory failed"))] {
+                match requests.recv().await {
+                    Some(pneuma_broker::handle::BrokerRequest::ResetEngine { reply }) => drop(reply.send(outcome)),
+                    _ => panic!("expected a reset request"),
+                }
+            }
+        });
+
+        let response = send(addr, b"POST /reset HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.contains(r#""reset":true"#), "{response}");
+        let response = send(addr, b"POST /reset HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 500 "), "{response}");
+        assert!(response.contains("reset failed: factory failed"), "{response}");
+        broker.await.expect("broker task");
+
+        let response = send(addr, b"GET /reset HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 "), "only POST resets: {response}");
+    }
+}
@@ -0,0 +1,30 @@
+use std::process::Command;
+
+#[test]
+fn doctor_fails_when_servo_bin_is_invalid() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pneuma"))
+        .arg("doctor")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("SERVO_WEBDRIVER_URL")
+        .env_remove("SERVO_SECONDARY_WEBDRIVER_URL")
+        .env_remove("SERVO_SECONDARY_WEBDRIVER_URLS")
+        .env("SERVO_BIN", "/nonexistent/pneuma-doctor/servo")
+        .env("PNEUMA_LOG", "off")
+        .output()
+        .expect("failed to run pneuma binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "expected doctor to fail.\nstdout:\n{stdout}\nstderr:\n{stderr}"
+    );
+    assert!(stdout.contains("[pass] config"), "config should still be checked.\nstdout:\n{stdout}");
+    assert!(stdout.contains("[pass] environment: set: SERVO_BIN"), "stdout:\n{stdout}");
+    assert!(
+        stdout.contains("[FAIL] servo binary") && stdout.contains("/nonexistent/pneuma-doctor/servo"),
+        "expected the failed binary check with the searched path.\nstdout:\n{stdout}"
+    );
+    assert!(stderr.contains("1 of 4 doctor checks failed"), "stderr:\n{stderr}");
+}
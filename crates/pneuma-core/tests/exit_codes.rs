@@ -0,0 +1,24 @@
+use std::process::Command;
+
+#[test]
+fn missing_servo_binary_exits_with_binary_not_found() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pneuma"))
+        .args(["eval", "1 + 1", "--engine", "servo"])
+        .env_remove("SERVO_WEBDRIVER_URL")
+        .env("SERVO_BIN", "/nonexistent/pneuma-test/servo")
+        .env("PNEUMA_LOG", "off")
+        .output()
+        .expect("failed to run pneuma binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        output.status.code(),
+        Some(5),
+        "expected binary-not-found exit code.\nstderr:\n{stderr}"
+    );
+    assert!(
+        stderr.contains("Servo was not found") && stderr.contains("/nonexistent/pneuma-test/servo"),
+        "expected the searched path in stderr.\nstderr:\n{stderr}"
+    );
+    assert!(stderr.contains("SERVO_BIN"), "expected an install hint in stderr.\nstderr:\n{stderr}");
+}
@@ -0,0 +1,34 @@
+use std::process::Command;
+
+#[test]
+#[ignore = "requires SERVO_BIN or SERVO_WEBDRIVER_URL"]
+fn probe_prints_report_for_data_url() {
+    let has_servo_env =
+        std::env::var("SERVO_BIN").is_ok() || std::env::var("SERVO_WEBDRIVER_URL").is_ok();
+    if !has_servo_env {
+        eprintln!("skipping: set SERVO_BIN or SERVO_WEBDRIVER_URL to run the probe smoke test");
+        return;
+    }
+
+    let url = "data:text/html,<title>Probe Fixture</title><main><p>offline probe</p></main>";
+    let output = Command::new(env!("CARGO_BIN_EXE_pneuma"))
+        .args(["probe", url, "--engine", "servo"])
+        .env("PNEUMA_LOG", "off")
+        .output()
+        .expect("failed to run pneuma binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "pneuma probe failed.\nstdout:\n{stdout}\nstderr:\n{stderr}"
+    );
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("probe output should be JSON");
+    assert_eq!(report["meta"]["title"], "Probe Fixture");
+    for key in ["paint_score", "dom_score", "js_score", "network_score", "overall", "decision"] {
+        assert!(report["report"].get(key).is_some(), "missing report.{key}: {report}");
+    }
+    assert!(report["signals"].get("dom_element_count").is_some());
+    assert!(report["explanation"].is_string(), "missing explanation: {report}");
+}
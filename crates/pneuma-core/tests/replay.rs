@@ -0,0 +1,60 @@
+use std::process::Command;
+
+#[test]
+fn replay_rescores_recorded_signals() {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/replay_signals.jsonl");
+    let output = Command::new(env!("CARGO_BIN_EXE_pneuma"))
+        .args(["replay", fixture])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("PNEUMA_LOG", "off")
+        .output()
+        .expect("failed to run pneuma binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "pneuma replay failed.\nstdout:\n{stdout}\nstderr:\n{stderr}"
+    );
+
+    let decisions: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each output line should be JSON"))
+        .collect();
+    let summary: Vec<(u64, &str)> = decisions
+        .iter()
+        .map(|decision| {
+            (
+                decision["line"].as_u64().unwrap(),
+                decision["decision"]["action"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [(1, "stay_on_servo"), (2, "escalate_to_ladybird"), (4, "stay_on_servo")],
+        "the blank line is skipped:\n{stdout}"
+    );
+    assert_eq!(decisions[1]["decision"]["detail"]["kind"], "zero_paint", "{stdout}");
+    assert!(decisions[1]["report"]["decision_trace"].is_array(), "{stdout}");
+}
+
+#[test]
+fn replay_names_the_line_it_cannot_parse() {
+    let dir = std::env::temp_dir().join(format!("pneuma-replay-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let signals = dir.join("broken.jsonl");
+    std::fs::write(&signals, "{\"title\": \"missing every count\"}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pneuma"))
+        .args(["replay".as_ref(), signals.as_os_str()])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("PNEUMA_LOG", "off")
+        .output()
+        .expect("failed to run pneuma binary");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "stderr:\n{stderr}");
+    assert!(stderr.contains("invalid signals on line 1"), "stderr:\n{stderr}");
+}
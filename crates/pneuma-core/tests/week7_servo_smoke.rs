@@ -18,6 +18,8 @@ fn week7_servo_smoke() {
 const nav = JSON.parse(__pneuma_private_ffi.navigate(1, "https://example.com", "{}"));
 const title = JSON.parse(__pneuma_private_ffi.evaluate(1, "document.title"));
 console.log("title:", title ?? nav.title ?? null);
+const page = __pneuma_private_ffi.evaluateJson(1, "({ title: document.title })");
+console.log("evaluateJson:", typeof page, page.title === title);
 "#;
     fs::write(&script_path, script).expect("failed to write smoke test script");
 
@@ -49,4 +51,8 @@ console.log("title:", title ?? nav.title ?? null);
         combined.contains("Example Domain"),
         "expected Example Domain in output.\ncombined:\n{combined}"
     );
+    assert!(
+        combined.contains("evaluateJson: object true"),
+        "expected evaluateJson to return a parsed object matching evaluate.\ncombined:\n{combined}"
+    );
 }
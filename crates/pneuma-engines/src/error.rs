@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+/// Typed engine failures that callers may want to match on.
+///
+/// Engines return `anyhow::Result`; recover these with
+/// `error.downcast_ref::<EngineError>()`, or use [`is_transient`] to classify.
+#[derive(Debug, thiserror::Error)]
+pub enum EngineError {
+    /// The operation was aborted via [`HeadlessEngine::cancel`](crate::HeadlessEngine::cancel).
+    #[error("engine operation cancelled")]
+    Cancelled,
+    /// The request never produced a response (connection refused/reset, timeout).
+    #[error("{0}")]
+    Transport(String),
+    /// The engine could not be started or reached: binary missing, process
+    /// died during startup, WebDriver never became ready or refused a session.
+    #[error("{0}")]
+    Unavailable(String),
+    /// The engine binary is neither where `SERVO_BIN` points nor on `PATH`.
+    /// `searched` lists every candidate path that was checked.
+    #[error(
+        "Servo binary not found (searched: {}); install Servo or set SERVO_BIN to the Servo executable",
+        display_paths(.searched)
+    )]
+    BinaryNotFound { searched: Vec<PathBuf> },
+    /// The engine did not reach the expected state in time.
+    #[error("{0}")]
+    Timeout(String),
+    /// No element matches the CSS selector an element command was given.
+    #[error("no element matches `{selector}`")]
+    ElementNotFound { selector: String },
+    /// The engine has no way to do what was asked, e.g. set request headers.
+    #[error("{0}")]
+    Unsupported(String),
+    /// The WebDriver endpoint answered with an error status.
+    #[error("{message}")]
+    WebDriver { status: u16, message: String },
+}
+
+impl EngineError {
+    /// Whether repeating the same operation may succeed: transport failures and
+    /// 5xx responses are transient, everything else (e.g. an invalid URL) is not.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            EngineError::Cancelled
+            | EngineError::Unavailable(_)
+            | EngineError::BinaryNotFound { .. }
+            | EngineError::Timeout(_)
+            | EngineError::ElementNotFound { .. }
+            | EngineError::Unsupported(_) => false,
+            EngineError::Transport(_) => true,
+            EngineError::WebDriver { status, .. } => *status >= 500,
+        }
+    }
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    if paths.is_empty() {
+        return "nothing, PATH is empty".to_string();
+    }
+    paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Classify an engine error by the first [`EngineError`] in its chain. Errors
+/// without one are treated as permanent.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<EngineError>())
+        .is_some_and(EngineError::is_transient)
+}
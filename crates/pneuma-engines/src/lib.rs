@@ -1,7 +1,13 @@
+pub mod error;
 pub mod ladybird;
 pub mod migration;
+pub mod navigate_opts;
 pub mod servo;
+pub mod timed;
 pub mod traits;
 
+pub use error::EngineError;
 pub use migration::{LocalStorageEntry, MigrationCookie, MigrationEnvelope};
-pub use traits::{EngineKind, HeadlessEngine};
+pub use navigate_opts::{url_without_credentials, BasicAuth, NavigateOptions, ReadyCondition, SettleCondition};
+pub use timed::{EngineTimeouts, TimedEngine};
+pub use traits::{EngineKind, HeadlessEngine, UnknownEngineKind};
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::EngineKind;
@@ -18,6 +20,49 @@ pub struct MigrationEnvelope {
     pub cookies: Vec<MigrationCookie>,
     /// Current-origin localStorage key/value pairs.
     pub local_storage: Vec<LocalStorageEntry>,
+    /// localStorage entries that were captured lossily: values that were not
+    /// strings were stringified, and lone UTF-16 surrogates (which JSON cannot
+    /// carry) were replaced with U+FFFD. These import with a different value
+    /// than the page originally stored.
+    #[serde(default)]
+    pub local_storage_coerced: u32,
+    /// localStorage entries dropped at capture because they had no usable key
+    /// or their value disappeared while being read.
+    #[serde(default)]
+    pub local_storage_skipped: u32,
+    /// Cookies left out at capture because WebDriver would refuse them on
+    /// import: values over the size limit or control characters in the name
+    /// or value.
+    #[serde(default)]
+    pub cookies_skipped: u32,
+}
+
+impl MigrationEnvelope {
+    /// Keep one cookie per `(name, domain, path)` and sort them by that key,
+    /// so an import sets the same cookies in the same order every time.
+    /// The cookie expiring last wins; when either has no expiry, the one
+    /// captured later does. Returns how many duplicates were dropped.
+    pub fn dedupe_cookies(&mut self) -> usize {
+        let before = self.cookies.len();
+        let mut winners: BTreeMap<(String, Option<String>, Option<String>), MigrationCookie> =
+            BTreeMap::new();
+        for cookie in std::mem::take(&mut self.cookies) {
+            let key = (
+                cookie.name.clone(),
+                cookie.domain.clone(),
+                cookie.path.clone(),
+            );
+            let outlives = |kept: &MigrationCookie| match (kept.expiry, cookie.expiry) {
+                (Some(kept), Some(new)) => kept > new,
+                _ => false,
+            };
+            if !winners.get(&key).is_some_and(outlives) {
+                winners.insert(key, cookie);
+            }
+        }
+        self.cookies = winners.into_values().collect();
+        before - self.cookies.len()
+    }
 }
 
 /// A single cookie transferred across engine instances.
@@ -48,4 +93,76 @@ pub struct MigrationCookie {
 pub struct LocalStorageEntry {
     pub key: String,
     pub value: String,
+    /// `location.origin` of the page the entry was read from, so import can
+    /// write it to the right origin. `None` in envelopes from before origins
+    /// were recorded, and for opaque origins; such entries go to whatever
+    /// page the importing engine is on.
+    #[serde(default)]
+    pub origin: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MigrationCookie, MigrationEnvelope};
+    use crate::EngineKind;
+
+    fn cookie(name: &str, value: &str, path: &str, expiry: Option<u64>) -> MigrationCookie {
+        MigrationCookie {
+            name: name.into(),
+            value: value.into(),
+            domain: Some("shop.example".into()),
+            path: Some(path.into()),
+            secure: None,
+            http_only: None,
+            expiry,
+            same_site: None,
+        }
+    }
+
+    #[test]
+    fn duplicate_cookies_collapse_to_one_deterministic_winner() {
+        let mut envelope = MigrationEnvelope {
+            source_engine: EngineKind::Servo,
+            captured_at_ms: 0,
+            current_url: None,
+            cookies: vec![
+                cookie("sid", "newest-expiry", "/", Some(2_000)),
+                cookie("cart", "first", "/", None),
+                cookie("sid", "older-expiry", "/", Some(1_000)),
+                cookie("sid", "other-path", "/account", None),
+                cookie("cart", "captured-later", "/", None),
+            ],
+            local_storage: vec![],
+            local_storage_coerced: 0,
+            local_storage_skipped: 0,
+            cookies_skipped: 0,
+        };
+        let mut reversed = envelope.clone();
+        reversed.cookies.reverse();
+
+        assert_eq!(envelope.dedupe_cookies(), 2);
+        let kept: Vec<(&str, &str)> = envelope
+            .cookies
+            .iter()
+            .map(|cookie| (cookie.name.as_str(), cookie.value.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                ("cart", "captured-later"),
+                ("sid", "newest-expiry"),
+                ("sid", "other-path")
+            ],
+            "one cookie per name, domain and path, sorted"
+        );
+
+        // Expiry decides regardless of capture order; only the expiry-less tie flips.
+        reversed.dedupe_cookies();
+        let kept: Vec<&str> = reversed
+            .cookies
+            .iter()
+            .map(|cookie| cookie.value.as_str())
+            .collect();
+        assert_eq!(kept, ["first", "newest-expiry", "other-path"]);
+    }
 }
@@ -1,3 +1,16 @@
 pub mod engine;
+pub mod poll;
+mod redirect_loop;
+mod stderr_tail;
+pub mod timeouts;
+pub mod transport;
 
-pub use engine::ServoEngine;
+pub use engine::{
+    check_endpoint, detached_servos, resolve_servo_binary, DetachedServo, ServoEngine, UnhandledPromptBehavior,
+    BINARY_RESULT_PREFIX, DEFAULT_MAX_COOKIE_VALUE_LEN,
+};
+pub use poll::PollSchedule;
+pub use timeouts::WebDriverTimeouts;
+pub use transport::{
+    Cassette, Exchange, HttpTransport, RecordingTransport, ReplayTransport, TracingTransport, WebDriverTransport,
+};
@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use rand::Rng;
+use std::time::Duration;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const INITIAL_POLL_DELAY: Duration = Duration::from_millis(25);
+
+/// Delay schedule for WebDriver readiness polling (startup `/status` checks and
+/// the post-navigate title loop).
+///
+/// Polling starts tight and doubles each attempt up to `interval`, so a fast
+/// endpoint is noticed quickly without hammering a slow one. Each delay gets
+/// up to `jitter` extra so that several engines polling one host spread out.
+///
+/// Configured via `PNEUMA_POLL_INTERVAL_MS` (the cap, default 200) and
+/// `PNEUMA_POLL_JITTER_MS` (default 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollSchedule {
+    pub interval: Duration,
+    pub jitter: Duration,
+}
+
+impl Default for PollSchedule {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_POLL_INTERVAL,
+            jitter: Duration::ZERO,
+        }
+    }
+}
+
+impl PollSchedule {
+    pub(crate) fn from_env() -> Result<Self> {
+        let mut schedule = Self::default();
+        if let Some(ms) = env_millis("PNEUMA_POLL_INTERVAL_MS")? {
+            if ms == 0 {
+                bail!("PNEUMA_POLL_INTERVAL_MS must be greater than zero");
+            }
+            schedule.interval = Duration::from_millis(ms);
+        }
+        if let Some(ms) = env_millis("PNEUMA_POLL_JITTER_MS")? {
+            schedule.jitter = Duration::from_millis(ms);
+        }
+        Ok(schedule)
+    }
+
+    /// Un-jittered delay before poll number `attempt` (0-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let initial = INITIAL_POLL_DELAY.min(self.interval);
+        initial
+            .checked_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .map_or(self.interval, |delay| delay.min(self.interval))
+    }
+
+    /// [`backoff`](Self::backoff) plus a random share of the configured jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.backoff(attempt);
+        if self.jitter.is_zero() {
+            return base;
+        }
+        base + rand::thread_rng().gen_range(Duration::ZERO..=self.jitter)
+    }
+}
+
+pub(super) fn env_millis(name: &str) -> Result<Option<u64>> {
+    match std::env::var(name) {
+        Ok(raw) if raw.trim().is_empty() => Ok(None),
+        Ok(raw) => match raw.trim().parse() {
+            Ok(ms) => Ok(Some(ms)),
+            Err(_) => bail!("{name} must be a whole number of milliseconds, got `{raw}`"),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PollSchedule;
+    use std::time::Duration;
+
+    #[test]
+    fn backoff_is_monotonic_up_to_cap() {
+        let schedule = PollSchedule {
+            interval: Duration::from_millis(300),
+            jitter: Duration::ZERO,
+        };
+        let delays: Vec<Duration> = (0..40).map(|attempt| schedule.backoff(attempt)).collect();
+
+        assert_eq!(delays[0], Duration::from_millis(25));
+        assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(delays.iter().all(|delay| *delay <= schedule.interval));
+        assert_eq!(*delays.last().unwrap(), schedule.interval);
+    }
+
+    #[test]
+    fn interval_below_initial_delay_is_flat() {
+        let schedule = PollSchedule {
+            interval: Duration::from_millis(10),
+            jitter: Duration::ZERO,
+        };
+        assert!((0..5).all(|attempt| schedule.backoff(attempt) == Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn jitter_stays_within_bound() {
+        let schedule = PollSchedule {
+            interval: Duration::from_millis(100),
+            jitter: Duration::from_millis(20),
+        };
+        for attempt in 0..20 {
+            let delay = schedule.delay(attempt);
+            let base = schedule.backoff(attempt);
+            assert!(delay >= base && delay <= base + schedule.jitter);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+/// URL changes within the window that count as a loop.
+pub(crate) const REDIRECT_LOOP_HOPS: usize = 6;
+
+/// Most distinct URLs a loop may cycle through; more than this looks like a
+/// long redirect chain rather than a loop.
+const MAX_LOOP_URLS: usize = 3;
+
+/// Watches `location.href` across readiness polls for a page bouncing among a
+/// few URLs, which would otherwise keep the title wait going until timeout.
+#[derive(Debug, Default)]
+pub(crate) struct RedirectLoopDetector {
+    /// The last `REDIRECT_LOOP_HOPS + 1` URLs seen, consecutive entries distinct.
+    recent: VecDeque<String>,
+}
+
+impl RedirectLoopDetector {
+    /// Record a sampled URL. Returns the URLs being cycled through, in first
+    /// seen order, once the last [`REDIRECT_LOOP_HOPS`] changes all stayed
+    /// within a set of at most three.
+    pub(crate) fn observe(&mut self, url: &str) -> Option<Vec<String>> {
+        if self.recent.back().is_some_and(|last| last == url) {
+            return None;
+        }
+        self.recent.push_back(url.to_string());
+        if self.recent.len() > REDIRECT_LOOP_HOPS + 1 {
+            self.recent.pop_front();
+        }
+        if self.recent.len() <= REDIRECT_LOOP_HOPS {
+            return None;
+        }
+        let mut cycle: Vec<String> = Vec::new();
+        for url in &self.recent {
+            if !cycle.contains(url) {
+                cycle.push(url.clone());
+            }
+        }
+        (cycle.len() <= MAX_LOOP_URLS).then_some(cycle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RedirectLoopDetector, REDIRECT_LOOP_HOPS};
+
+    #[test]
+    fn flags_oscillation_but_not_chains_or_settled_pages() {
+        let mut bouncing = RedirectLoopDetector::default();
+        let urls = ["https://a.example/", "https://b.example/"];
+        let flagged: Vec<_> = (0..=REDIRECT_LOOP_HOPS)
+            .map(|hop| bouncing.observe(urls[hop % 2]))
+            .collect();
+        assert!(flagged[..REDIRECT_LOOP_HOPS].iter().all(Option::is_none));
+        assert_eq!(flagged[REDIRECT_LOOP_HOPS].as_deref(), Some(&urls.map(String::from)[..]));
+
+        let mut settled = RedirectLoopDetector::default();
+        for _ in 0..20 {
+            assert_eq!(settled.observe("https://a.example/"), None);
+        }
+
+        let mut chain = RedirectLoopDetector::default();
+        for hop in 0..20 {
+            assert_eq!(chain.observe(&format!("https://example.com/{hop}")), None);
+        }
+    }
+}
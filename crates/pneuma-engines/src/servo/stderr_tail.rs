@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Child;
+use tokio::task::JoinHandle;
+
+/// Lines of Servo stderr kept for startup error messages.
+const TAIL_LINES: usize = 20;
+
+/// How long to wait for buffered stderr after the process has exited.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Keeps the last [`TAIL_LINES`] lines a spawned Servo wrote to stderr, and
+/// streams every line to `tracing` at debug level.
+///
+/// Reading continues for the life of the process so the pipe never fills up.
+pub(crate) struct StderrTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl StderrTail {
+    /// Start reading the child's stderr. The child must have been spawned with
+    /// `Stdio::piped()` for stderr; otherwise the tail stays empty.
+    pub(crate) fn capture(child: &mut Child) -> Self {
+        let lines: Arc<Mutex<VecDeque<String>>> = Arc::default();
+        let reader = child.stderr.take().map(|stderr| {
+            let lines = lines.clone();
+            tokio::spawn(async move {
+                // Bytes, not `lines()`: a line that is not UTF-8 would end
+                // the read and leave the pipe to fill up.
+                let mut reader = BufReader::new(stderr);
+                let mut buf = Vec::new();
+                loop {
+                    buf.clear();
+                    match reader.read_until(b'\n', &mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => {}
+                    }
+                    let line = String::from_utf8_lossy(&buf).trim_end_matches(['\n', '\r']).to_string();
+                    tracing::debug!(target: "pneuma_engines", line = %line, "servo stderr");
+                    let mut tail = lines.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    if tail.len() == TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
+                }
+            })
+        });
+        Self {
+            lines,
+            reader: Mutex::new(reader),
+        }
+    }
+
+    /// Wait (briefly) for the reader to hit end of stream, so output written
+    /// just before the process exited is included.
+    pub(crate) async fn drained(&self) {
+        let reader = self
+            .reader
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(reader) = reader {
+            let _ = tokio::time::timeout(DRAIN_TIMEOUT, reader).await;
+        }
+    }
+
+    /// The captured lines as a suffix for an error message; empty when Servo
+    /// wrote nothing.
+    pub(crate) fn render(&self) -> String {
+        let tail = self.lines.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if tail.is_empty() {
+            return String::new();
+        }
+        let mut rendered = String::from("\nlast Servo stderr output:");
+        for line in tail.iter() {
+            rendered.push_str("\n  ");
+            rendered.push_str(line);
+        }
+        rendered
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use super::poll::env_millis;
+
+/// WebDriver session timeouts (W3C WebDriver §9 "Timeouts").
+///
+/// `None` leaves the endpoint's default in place. `script` bounds
+/// `execute/sync` and `execute/async`; `page_load` bounds navigation;
+/// `implicit` is the element-lookup wait.
+///
+/// Configured via `PNEUMA_WD_SCRIPT_TIMEOUT_MS`, `PNEUMA_WD_PAGE_LOAD_TIMEOUT_MS`
+/// and `PNEUMA_WD_IMPLICIT_TIMEOUT_MS`; whatever is set is applied once when
+/// the session is created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WebDriverTimeouts {
+    pub script: Option<Duration>,
+    pub page_load: Option<Duration>,
+    pub implicit: Option<Duration>,
+}
+
+impl WebDriverTimeouts {
+    pub(crate) fn from_env() -> Result<Self> {
+        Ok(Self {
+            script: env_millis("PNEUMA_WD_SCRIPT_TIMEOUT_MS")?.map(Duration::from_millis),
+            page_load: env_millis("PNEUMA_WD_PAGE_LOAD_TIMEOUT_MS")?.map(Duration::from_millis),
+            implicit: env_millis("PNEUMA_WD_IMPLICIT_TIMEOUT_MS")?.map(Duration::from_millis),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.script.is_none() && self.page_load.is_none() && self.implicit.is_none()
+    }
+
+    /// Body for `POST /session/{id}/timeouts`, carrying only the set fields.
+    pub fn to_payload(&self) -> Value {
+        let mut payload = Map::new();
+        for (key, timeout) in [
+            ("script", self.script),
+            ("pageLoad", self.page_load),
+            ("implicit", self.implicit),
+        ] {
+            if let Some(timeout) = timeout {
+                let ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+                payload.insert(key.into(), ms.into());
+            }
+        }
+        Value::Object(payload)
+    }
+
+    /// Parse the value of `GET /session/{id}/timeouts`. A `null` script
+    /// timeout (no limit) reads as `None`.
+    pub(crate) fn from_wd_value(value: &Value) -> Result<Self> {
+        let field = |key: &str| -> Result<Option<Duration>> {
+            match value.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(ms) => ms
+                    .as_u64()
+                    .map(|ms| Some(Duration::from_millis(ms)))
+                    .with_context(|| format!("timeouts `{key}` is not a whole number of milliseconds: {ms}")),
+            }
+        };
+        Ok(Self {
+            script: field("script")?,
+            page_load: field("pageLoad")?,
+            implicit: field("implicit")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WebDriverTimeouts;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn payload_carries_only_configured_timeouts() {
+        assert!(WebDriverTimeouts::default().is_empty());
+        assert_eq!(WebDriverTimeouts::default().to_payload(), json!({}));
+
+        let timeouts = WebDriverTimeouts {
+            script: Some(Duration::from_secs(30)),
+            page_load: None,
+            implicit: Some(Duration::ZERO),
+        };
+        assert_eq!(timeouts.to_payload(), json!({ "script": 30000, "implicit": 0 }));
+    }
+
+    #[test]
+    fn parses_webdriver_timeouts_value() {
+        let parsed =
+            WebDriverTimeouts::from_wd_value(&json!({ "script": null, "pageLoad": 300000, "implicit": 0 }))
+                .unwrap();
+        assert_eq!(
+            parsed,
+            WebDriverTimeouts {
+                script: None,
+                page_load: Some(Duration::from_secs(300)),
+                implicit: Some(Duration::ZERO),
+            }
+        );
+        assert!(WebDriverTimeouts::from_wd_value(&json!({ "script": "soon" })).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::EngineError;
+use crate::migration::{MigrationCookie, MigrationEnvelope};
+use crate::traits::{EngineKind, HeadlessEngine};
+
+/// Per-method limits applied by [`TimedEngine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineTimeouts {
+    pub navigate: Duration,
+    pub evaluate: Duration,
+    pub screenshot: Duration,
+    /// `get_cookies`, `extract_state`, `import_state` and `reset_state`.
+    pub state: Duration,
+    /// Everything else: windows, current URL, init scripts and `close`.
+    pub control: Duration,
+}
+
+impl EngineTimeouts {
+    /// The same limits with `limit` for every method but `navigate`.
+    pub fn with_operation_limit(self, limit: Duration) -> Self {
+        Self {
+            navigate: self.navigate,
+            evaluate: limit,
+            screenshot: limit,
+            state: limit,
+            control: limit,
+        }
+    }
+}
+
+impl Default for EngineTimeouts {
+    fn default() -> Self {
+        Self {
+            navigate: Duration::from_secs(60),
+            evaluate: Duration::from_secs(30),
+            screenshot: Duration::from_secs(30),
+            state: Duration::from_secs(30),
+            control: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Wraps any engine so that no call can hang past its limit in
+/// [`EngineTimeouts`]; a call that does fails with [`EngineError::Timeout`].
+/// The inner call is dropped at the deadline, not cancelled engine-side.
+pub struct TimedEngine {
+    inner: Box<dyn HeadlessEngine>,
+    timeouts: EngineTimeouts,
+}
+
+impl TimedEngine {
+    pub fn new(inner: Box<dyn HeadlessEngine>, timeouts: EngineTimeouts) -> Self {
+        Self { inner, timeouts }
+    }
+
+    pub fn timeouts(&self) -> EngineTimeouts {
+        self.timeouts
+    }
+
+    async fn timed<T>(
+        &self,
+        operation: &str,
+        limit: Duration,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        match tokio::time::timeout(limit, call).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::warn!(
+                    target: "pneuma_engines",
+                    engine_instance = self.inner.instance_id(),
+                    operation,
+                    limit_ms = u64::try_from(limit.as_millis()).unwrap_or(u64::MAX),
+                    "engine operation timed out"
+                );
+                Err(EngineError::Timeout(format!("{operation} timed out after {limit:?}")).into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl HeadlessEngine for TimedEngine {
+    fn kind(&self) -> EngineKind {
+        self.inner.kind()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn instance_id(&self) -> &str {
+        self.inner.instance_id()
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    async fn navigate(&self, url: &str, opts_json: &str) -> anyhow::Result<String> {
+        self.timed("navigate", self.timeouts.navigate, self.inner.navigate(url, opts_json))
+            .await
+    }
+
+    async fn evaluate(&self, script: &str) -> anyhow::Result<String> {
+        self.timed("evaluate", self.timeouts.evaluate, self.inner.evaluate(script))
+            .await
+    }
+
+    async fn screenshot(&self) -> anyhow::Result<Vec<u8>> {
+        self.timed("screenshot", self.timeouts.screenshot, self.inner.screenshot())
+            .await
+    }
+
+    async fn screenshot_element(&self, selector: &str) -> anyhow::Result<Vec<u8>> {
+        self.timed(
+            "screenshot_element",
+            self.timeouts.screenshot,
+            self.inner.screenshot_element(selector),
+        )
+        .await
+    }
+
+    async fn close(&self) -> anyhow::Result<()> {
+        self.timed("close", self.timeouts.control, self.inner.close()).await
+    }
+
+    async fn add_init_script(&self, script: &str) -> anyhow::Result<()> {
+        self.timed(
+            "add_init_script",
+            self.timeouts.control,
+            self.inner.add_init_script(script),
+        )
+        .await
+    }
+
+    async fn window_handles(&self) -> anyhow::Result<Vec<String>> {
+        self.timed("window_handles", self.timeouts.control, self.inner.window_handles())
+            .await
+    }
+
+    async fn open_window(&self) -> anyhow::Result<String> {
+        self.timed("open_window", self.timeouts.control, self.inner.open_window())
+            .await
+    }
+
+    async fn switch_to_window(&self, handle: &str) -> anyhow::Result<()> {
+        self.timed(
+            "switch_to_window",
+            self.timeouts.control,
+            self.inner.switch_to_window(handle),
+        )
+        .await
+    }
+
+    async fn close_window(&self, handle: &str) -> anyhow::Result<()> {
+        self.timed("close_window", self.timeouts.control, self.inner.close_window(handle))
+            .await
+    }
+
+    async fn current_url(&self) -> anyhow::Result<Option<String>> {
+        self.timed("current_url", self.timeouts.control, self.inner.current_url())
+            .await
+    }
+
+    async fn get_cookies(&self) -> anyhow::Result<Vec<MigrationCookie>> {
+        self.timed("get_cookies", self.timeouts.state, self.inner.get_cookies())
+            .await
+    }
+
+    async fn extract_state(&self) -> anyhow::Result<MigrationEnvelope> {
+        self.timed("extract_state", self.timeouts.state, self.inner.extract_state())
+            .await
+    }
+
+    async fn import_state(&self, state: MigrationEnvelope) -> anyhow::Result<()> {
+        self.timed("import_state", self.timeouts.state, self.inner.import_state(state))
+            .await
+    }
+
+    async fn reset_state(&self) -> anyhow::Result<()> {
+        self.timed("reset_state", self.timeouts.state, self.inner.reset_state())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EngineTimeouts, TimedEngine};
+    use crate::{EngineError, EngineKind, HeadlessEngine, MigrationEnvelope};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Navigates instantly; every evaluate hangs for a minute.
+    struct HangingEvaluate;
+
+    #[async_trait]
+    impl HeadlessEngine for HangingEvaluate {
+        fn kind(&self) -> EngineKind {
+            EngineKind::Servo
+        }
+        fn name(&self) -> &'static str {
+            "hanging"
+        }
+        async fn navigate(&self, _url: &str, _opts_json: &str) -> anyhow::Result<String> {
+            Ok(r#"{"ok":true}"#.into())
+        }
+        async fn evaluate(&self, _script: &str) -> anyhow::Result<String> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok("null".into())
+        }
+        async fn screenshot(&self) -> anyhow::Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+        async fn close(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn extract_state(&self) -> anyhow::Result<MigrationEnvelope> {
+            anyhow::bail!("not captured")
+        }
+        async fn import_state(&self, _state: MigrationEnvelope) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_wrapped_method_times_out() {
+        let timeouts = EngineTimeouts {
+            evaluate: Duration::from_millis(50),
+            ..EngineTimeouts::default()
+        };
+        let engine = TimedEngine::new(Box::new(HangingEvaluate), timeouts);
+
+        assert_eq!(
+            engine.navigate("https://example.com/", "{}").await.unwrap(),
+            r#"{"ok":true}"#
+        );
+
+        let started = std::time::Instant::now();
+        let error = engine.evaluate("1 + 1").await.expect_err("evaluate should time out");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        match error.downcast_ref::<EngineError>() {
+            Some(EngineError::Timeout(message)) => assert!(message.starts_with("evaluate timed out"), "{message}"),
+            other => panic!("expected a timeout error, got {other:?}"),
+        }
+        assert_eq!(engine.name(), "hanging", "identity is delegated");
+    }
+}
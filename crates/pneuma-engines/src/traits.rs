@@ -1,9 +1,9 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::migration::MigrationEnvelope;
+use crate::migration::{MigrationCookie, MigrationEnvelope};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EngineKind {
     Servo,
@@ -20,15 +20,114 @@ impl std::fmt::Display for EngineKind {
     }
 }
 
+/// Returned when parsing an [`EngineKind`] from an unrecognised name.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown engine `{0}`; expected one of: servo, ladybird")]
+pub struct UnknownEngineKind(pub String);
+
+/// Parses the names [`Display`](std::fmt::Display) produces, ignoring case and
+/// surrounding whitespace.
+impl std::str::FromStr for EngineKind {
+    type Err = UnknownEngineKind;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        if trimmed.eq_ignore_ascii_case("servo") {
+            Ok(EngineKind::Servo)
+        } else if trimmed.eq_ignore_ascii_case("ladybird") {
+            Ok(EngineKind::Ladybird)
+        } else {
+            Err(UnknownEngineKind(value.to_string()))
+        }
+    }
+}
+
 #[async_trait]
 pub trait HeadlessEngine: Send + Sync {
     fn kind(&self) -> EngineKind;
     fn name(&self) -> &'static str;
+
+    /// Identifier that distinguishes this engine instance from other instances
+    /// of the same kind (e.g. a primary and a secondary Servo both report
+    /// `name() == "servo"`). Intended for logs; the format is not stable.
+    fn instance_id(&self) -> &str {
+        self.name()
+    }
+
+    /// Abort in-flight long-running operations (navigation readiness polling,
+    /// startup waits). Pending and subsequent calls fail with
+    /// [`EngineError::Cancelled`](crate::EngineError::Cancelled); `close` still
+    /// works. Cancellation is permanent for the instance. Default: no-op.
+    fn cancel(&self) {}
+
+    /// Navigate the current window to `url`. `opts_json` carries script options;
+    /// the keys engines act on are described by [`NavigateOptions`](crate::NavigateOptions).
     async fn navigate(&self, url: &str, opts_json: &str) -> anyhow::Result<String>;
+    /// Evaluate `script` in the current document and return its value as JSON
+    /// text. WebDriver has no `undefined`, so a script evaluating to
+    /// `undefined` and one evaluating to `null` both yield `"null"`; callers
+    /// that need the difference should return it explicitly (e.g.
+    /// `typeof x === "undefined" ? { undefined: true } : x`).
     async fn evaluate(&self, script: &str) -> anyhow::Result<String>;
     async fn screenshot(&self) -> anyhow::Result<Vec<u8>>;
     async fn close(&self) -> anyhow::Result<()>;
 
+    /// PNG of the first element matching the CSS `selector` in the current
+    /// document. Fails with [`EngineError::ElementNotFound`](crate::EngineError::ElementNotFound)
+    /// when nothing matches. Default: unsupported.
+    async fn screenshot_element(&self, selector: &str) -> anyhow::Result<Vec<u8>> {
+        let _ = selector;
+        Err(crate::EngineError::Unsupported(format!("{} does not support element screenshots", self.name())).into())
+    }
+
+    /// Register `script` to run on every document loaded by later navigations,
+    /// so patches are in place before the caller sees the page. Engines
+    /// without a native hook re-inject it at the start of each navigate.
+    async fn add_init_script(&self, script: &str) -> anyhow::Result<()> {
+        let _ = script;
+        anyhow::bail!("{} does not support init scripts", self.name())
+    }
+
+    /// Handles of the top-level windows open in this engine's session, in the
+    /// order the engine reports them. An empty list means the engine has a
+    /// single implicit window and does not support switching. Default: empty.
+    async fn window_handles(&self) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Open a new top-level window and return its handle. The current window
+    /// is left unchanged.
+    async fn open_window(&self) -> anyhow::Result<String> {
+        anyhow::bail!("{} does not support multiple windows", self.name())
+    }
+
+    /// Make `handle` the target of subsequent navigate/evaluate/screenshot calls.
+    async fn switch_to_window(&self, handle: &str) -> anyhow::Result<()> {
+        let _ = handle;
+        anyhow::bail!("{} does not support window switching", self.name())
+    }
+
+    /// Close the window `handle`. No window is current afterwards; switch to
+    /// another before the next call.
+    async fn close_window(&self, handle: &str) -> anyhow::Result<()> {
+        let _ = handle;
+        anyhow::bail!("{} does not support closing windows", self.name())
+    }
+
+    /// URL of the current window's document, without a full state capture.
+    /// `None` when there is no document URL or the engine cannot tell cheaply.
+    /// Default: `None`.
+    async fn current_url(&self) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Cookies visible to the current window's document, without capturing
+    /// storage or the URL. Default: the cookies of a full
+    /// [`extract_state`](Self::extract_state).
+    async fn get_cookies(&self) -> anyhow::Result<Vec<MigrationCookie>> {
+        Ok(self.extract_state().await?.cookies)
+    }
+
     /// Capture cookies and current-origin localStorage into a portable envelope.
     ///
     /// Implementations should make a best-effort capture; partial results are
@@ -40,7 +139,41 @@ pub trait HeadlessEngine: Send + Sync {
     ///
     /// The engine must already be on a page in the target origin before
     /// `import_state` is called (so that cookie domain and localStorage context
-    /// are valid). Partial import failures are logged but do not cause an `Err`
-    /// return unless the whole operation is unrecoverable.
+    /// are valid). localStorage entries recorded for an origin other than that
+    /// of the envelope's `current_url` are skipped. Partial import failures are
+    /// logged but do not cause an `Err` return unless the whole operation is
+    /// unrecoverable.
     async fn import_state(&self, state: MigrationEnvelope) -> anyhow::Result<()>;
+
+    /// Drop the current origin's state so the session can be reused for an
+    /// unrelated task: delete the cookies the current document can see, clear
+    /// its storage and leave the window on `about:blank`. WebDriver reaches no
+    /// other origin, so cookies and storage other origins set survive; a
+    /// fresh engine is the only clean slate. Steps the engine cannot perform
+    /// are skipped; an `Err` means the session itself is gone.
+    async fn reset_state(&self) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support state reset", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EngineKind, UnknownEngineKind};
+
+    #[test]
+    fn engine_kind_parses_display_names_case_insensitively() {
+        for kind in [EngineKind::Servo, EngineKind::Ladybird] {
+            assert_eq!(kind.to_string().parse::<EngineKind>(), Ok(kind));
+        }
+        assert_eq!("SERVO".parse::<EngineKind>(), Ok(EngineKind::Servo));
+        assert_eq!(" Ladybird ".parse::<EngineKind>(), Ok(EngineKind::Ladybird));
+    }
+
+    #[test]
+    fn engine_kind_rejects_unknown_names() {
+        let error = "chromium".parse::<EngineKind>().unwrap_err();
+        assert_eq!(error, UnknownEngineKind("chromium".into()));
+        assert_eq!(error.to_string(), "unknown engine `chromium`; expected one of: servo, ladybird");
+        assert!("".parse::<EngineKind>().is_err());
+    }
 }
@@ -0,0 +1,24 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use pneuma_engines::servo::{ReplayTransport, ServoEngine};
+use pneuma_engines::HeadlessEngine;
+
+#[tokio::test]
+async fn navigate_replays_a_recorded_session() {
+    let cassette = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/servo_navigate.cassette.json");
+    let replay = Arc::new(ReplayTransport::from_file(Path::new(cassette)).expect("cassette should load"));
+
+    let engine = ServoEngine::launch_with_transport(Arc::clone(&replay), "http://127.0.0.1:4444".into())
+        .await
+        .expect("session should start from the recording");
+    let meta = engine.navigate("https://example.com/", "{}").await.expect("navigate should replay");
+    let meta: serde_json::Value = serde_json::from_str(&meta).unwrap();
+    assert_eq!(meta["title"], "Example Domain");
+    assert_eq!(meta["final_url"], "https://example.com/");
+    assert_eq!(meta["content_type"], "text/html");
+    assert_eq!(meta["dom_element_count"], 13);
+
+    engine.close().await.expect("close should replay");
+    assert_eq!(replay.remaining(), 0, "every recorded exchange is used");
+}
@@ -0,0 +1,170 @@
+//! Which scripts a runtime may send to the page. Consulted by the FFI bridge
+//! before every evaluate and every `javascript:` navigate. A sandboxed
+//! runtime refuses evaluates outright unless its policy has a hash
+//! allowlist; with one, it can run exactly the registered script templates.
+
+use std::collections::HashSet;
+use std::fmt::{self, Write as _};
+
+/// Scripts the FFI bridge lets through. The default allows everything.
+///
+/// A script is rejected when an allowlist is set and its SHA-256 is not on
+/// it, or when it contains a denied pattern. Patterns match with whitespace
+/// ignored, so `while(true)` also catches `while (true)`. They are a tripwire
+/// for obvious mistakes, not a parser: any script can be rewritten to avoid
+/// them, which is what the hash allowlist is for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvalPolicy {
+    allowed_hashes: Option<HashSet<String>>,
+    denied_patterns: Vec<String>,
+}
+
+/// Why [`EvalPolicy::check`] refused a script; thrown to JS as its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalRejected {
+    NotAllowed { hash: String },
+    DeniedPattern { pattern: String },
+}
+
+impl fmt::Display for EvalRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAllowed { hash } => write!(f, "script sha256:{hash} is not on the evaluate allowlist"),
+            Self::DeniedPattern { pattern } => write!(f, "script matches denied pattern `{pattern}`"),
+        }
+    }
+}
+
+impl std::error::Error for EvalRejected {}
+
+impl EvalPolicy {
+    /// Allow only scripts whose hex SHA-256 (see [`script_hash`](Self::script_hash))
+    /// is in `hashes`. Case-insensitive.
+    pub fn allow_hashes(mut self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let allowed = self.allowed_hashes.get_or_insert_with(HashSet::new);
+        allowed.extend(hashes.into_iter().map(|hash| hash.as_ref().trim().to_ascii_lowercase()));
+        self
+    }
+
+    /// Allow exactly `script`, e.g. a registered template.
+    pub fn allow_script(self, script: &str) -> Self {
+        self.allow_hashes([Self::script_hash(script)])
+    }
+
+    /// Reject scripts containing `pattern`, whitespace ignored.
+    pub fn deny_pattern(mut self, pattern: &str) -> Self {
+        let pattern = strip_whitespace(pattern);
+        if !pattern.is_empty() {
+            self.denied_patterns.push(pattern);
+        }
+        self
+    }
+
+    /// Whether every script passes.
+    pub fn is_allow_all(&self) -> bool {
+        self.allowed_hashes.is_none() && self.denied_patterns.is_empty()
+    }
+
+    /// Whether only allowlisted scripts pass, as a sandboxed runtime requires
+    /// before it lets any evaluate through.
+    pub fn has_allowlist(&self) -> bool {
+        self.allowed_hashes.is_some()
+    }
+
+    /// Lowercase hex SHA-256 of `script`, as the allowlist holds it.
+    pub fn script_hash(script: &str) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, script.as_bytes());
+        digest.as_ref().iter().fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+
+    pub fn check(&self, script: &str) -> Result<(), EvalRejected> {
+        if !self.denied_patterns.is_empty() {
+            let compact = strip_whitespace(script);
+            if let Some(pattern) = self
+                .denied_patterns
+                .iter()
+                .find(|pattern| compact.contains(pattern.as_str()))
+            {
+                return Err(EvalRejected::DeniedPattern {
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+        if let Some(allowed) = &self.allowed_hashes {
+            let hash = Self::script_hash(script);
+            if !allowed.contains(&hash) {
+                return Err(EvalRejected::NotAllowed { hash });
+            }
+        }
+        Ok(())
+    }
+
+    /// [`check`](Self::check) the script of a `javascript:` URL; other URLs
+    /// run nothing and pass.
+    pub fn check_navigate(&self, url: &str) -> Result<(), EvalRejected> {
+        let trimmed = url.trim_start();
+        match trimmed.get(..11) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("javascript:") => self.check(&trimmed[11..]),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn strip_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EvalPolicy, EvalRejected};
+
+    #[test]
+    fn allowlisted_hash_passes_and_others_are_rejected() {
+        let template = "document.title";
+        let policy = EvalPolicy::default().allow_hashes([EvalPolicy::script_hash(template).to_uppercase()]);
+
+        assert_eq!(policy.check(template), Ok(()));
+        assert_eq!(
+            policy.check("document.cookie"),
+            Err(EvalRejected::NotAllowed {
+                hash: EvalPolicy::script_hash("document.cookie")
+            })
+        );
+        assert_eq!(
+            EvalPolicy::script_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert!(
+            policy.check_navigate("https://example.com/").is_ok(),
+            "plain URLs run no script"
+        );
+        assert!(policy.check_navigate("JavaScript:document.cookie").is_err());
+    }
+
+    #[test]
+    fn denied_pattern_matches_regardless_of_whitespace() {
+        let policy = EvalPolicy::default()
+            .deny_pattern("while(true)")
+            .allow_script("while ( true ) {}");
+
+        assert_eq!(
+            policy.check("while ( true ) {}"),
+            Err(EvalRejected::DeniedPattern {
+                pattern: "while(true)".into()
+            }),
+            "a denial wins over the allowlist"
+        );
+        assert!(EvalPolicy::default()
+            .deny_pattern("for(;;)")
+            .check("for (let i = 0; i < 3; i++) {}")
+            .is_ok());
+        assert!(EvalPolicy::default().is_allow_all());
+        assert!(
+            EvalPolicy::default().check("while (true) {}").is_ok(),
+            "the default allows everything"
+        );
+    }
+}
@@ -1,18 +1,198 @@
 #[cfg(feature = "quickjs")]
-use pneuma_broker::handle::BrokerHandle;
+use crate::eval_policy::EvalPolicy;
 #[cfg(feature = "quickjs")]
-use rquickjs::{Ctx, Function, Object, Result, Undefined};
+use crate::script_root::ScriptRoot;
+#[cfg(feature = "quickjs")]
+use pneuma_broker::handle::{BrokerHandle, NavigateError, PendingReply};
+#[cfg(feature = "quickjs")]
+use pneuma_broker::migration::MigrationEnvelope;
+#[cfg(feature = "quickjs")]
+use rquickjs::{ArrayBuffer, Ctx, Exception, Function, IntoJs, Object, Persistent, Result, Undefined, Value};
+#[cfg(feature = "quickjs")]
+use std::cell::{Cell, RefCell};
+#[cfg(feature = "quickjs")]
+use std::rc::Rc;
+
+/// Which host functions a runtime's FFI exposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfiMode {
+    /// Everything, for trusted scripts run by the CLI.
+    Full,
+    /// For untrusted scripts: only the functions in [`SANDBOX_ALLOWED`] work,
+    /// plus [`SANDBOX_VETTED`] under a policy with an allowlist; every other
+    /// one throws a JS error instead of reaching the host.
+    Sandboxed,
+}
+
+/// Host functions a sandboxed runtime keeps: creating and navigating pages,
+/// plus `log` so `console` works. Everything else throws, which covers
+/// `exit` (it would end the host process), `print` (the host's stdout),
+/// `evaluateFile` (the host's files), evaluates other than
+/// [`SANDBOX_VETTED`], cookies and screenshots, and any function added later.
+pub const SANDBOX_ALLOWED: &[&str] = &["log", "createPage", "createPageWithState", "navigate", "navigateAsync"];
+
+/// Evaluates a sandboxed runtime also keeps when its
+/// [`EvalPolicy`](crate::eval_policy::EvalPolicy) has a hash allowlist, so
+/// only registered scripts reach a page. Without one they throw like the rest.
+pub const SANDBOX_VETTED: &[&str] = &["evaluate", "evaluateAsync", "evaluateJson"];
+
+/// Tail of the message every refused host function throws.
+pub(crate) const SANDBOX_REFUSAL: &str = " is not available in sandbox mode";
 
 #[cfg(feature = "quickjs")]
 fn to_js_err(error: anyhow::Error) -> rquickjs::Error {
     rquickjs::Error::new_from_js_message("broker", "js", error.to_string())
 }
 
-/// Registers all `__pneuma_private_ffi` host functions into the QuickJS context.
-/// Must be called BEFORE the ghost_shim.js is evaluated.
+/// A JS `Error` for a failed navigate, with `kind` (see
+/// [`NavigateError::kind`]) and `retryable` set so scripts can tell a
+/// transient failure from a fatal one.
+#[cfg(feature = "quickjs")]
+fn navigate_exception<'js>(ctx: &Ctx<'js>, error: &NavigateError) -> Result<Exception<'js>> {
+    let exception = Exception::from_message(ctx.clone(), &error.to_string())?;
+    exception.as_object().set("kind", error.kind())?;
+    exception.as_object().set("retryable", error.is_retryable())?;
+    Ok(exception)
+}
+
+/// `Ok(())` unless `policy` refuses `script`, in which case a JS error
+/// naming the reason.
+#[cfg(feature = "quickjs")]
+fn vet_script(policy: &EvalPolicy, script: &str) -> Result<()> {
+    policy.check(script).map_err(|rejected| {
+        tracing::warn!(target: "ghost_shim", error = %rejected, "refused script by evaluate policy");
+        to_js_err(rejected.into())
+    })
+}
+
+/// Like [`vet_script`] for the script a `javascript:` URL would run.
+#[cfg(feature = "quickjs")]
+fn vet_navigate(policy: &EvalPolicy, url: &str) -> Result<()> {
+    policy.check_navigate(url).map_err(|rejected| {
+        tracing::warn!(target: "ghost_shim", error = %rejected, "refused navigate by evaluate policy");
+        to_js_err(rejected.into())
+    })
+}
+
+/// Broker calls made through the promise-returning FFI (`navigateAsync`,
+/// `evaluateAsync`) whose promises are not settled yet. The runtime thread
+/// settles them from its event loop as the broker replies.
+#[cfg(feature = "quickjs")]
+#[derive(Clone, Default)]
+pub struct PendingCalls(Rc<RefCell<Vec<PendingCall>>>);
+
+#[cfg(feature = "quickjs")]
+struct PendingCall {
+    reply: PendingReply<String>,
+    resolve: Persistent<Function<'static>>,
+    reject: Persistent<Function<'static>>,
+    /// Rejects with a [`navigate_exception`] rather than a plain `Error`.
+    navigate: bool,
+}
+
+#[cfg(feature = "quickjs")]
+impl PendingCalls {
+    /// A promise settled with `reply` once it arrives.
+    fn promise<'js>(&self, ctx: &Ctx<'js>, reply: PendingReply<String>) -> Result<Value<'js>> {
+        self.track(ctx, reply, false)
+    }
+
+    /// Like [`promise`](Self::promise), for a navigate reply.
+    fn navigate_promise<'js>(&self, ctx: &Ctx<'js>, reply: PendingReply<String>) -> Result<Value<'js>> {
+        self.track(ctx, reply, true)
+    }
+
+    fn track<'js>(&self, ctx: &Ctx<'js>, reply: PendingReply<String>, navigate: bool) -> Result<Value<'js>> {
+        let (promise, resolve, reject) = ctx.promise()?;
+        self.0.borrow_mut().push(PendingCall {
+            reply,
+            resolve: Persistent::save(ctx, resolve),
+            reject: Persistent::save(ctx, reject),
+            navigate,
+        });
+        promise.into_js(ctx)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Settle every call whose reply has arrived. When none has, block on the
+    /// oldest first, so each call makes progress.
+    pub fn settle(&self, ctx: &Ctx<'_>) -> Result<()> {
+        let mut settled = Vec::new();
+        let oldest = {
+            let mut calls = self.0.borrow_mut();
+            let mut index = 0;
+            while index < calls.len() {
+                match calls[index].reply.try_take() {
+                    Some(result) => {
+                        let call = calls.remove(index);
+                        settled.push((call.resolve, call.reject, call.navigate, result));
+                    }
+                    None => index += 1,
+                }
+            }
+            (settled.is_empty() && !calls.is_empty()).then(|| calls.remove(0))
+        };
+        if let Some(call) = oldest {
+            settled.push((call.resolve, call.reject, call.navigate, call.reply.wait()));
+        }
+
+        for (resolve, reject, navigate, result) in settled {
+            match result {
+                Ok(raw) => resolve.restore(ctx)?.call::<_, ()>((raw,))?,
+                Err(error) => {
+                    let exception = if navigate {
+                        navigate_exception(ctx, &NavigateError::from(error))?
+                    } else {
+                        Exception::from_message(ctx.clone(), &error.to_string())?
+                    };
+                    reject.restore(ctx)?.call::<_, ()>((exception,))?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop unsettled calls; their promises never settle.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+/// The code a sandboxed `ghost.exit(code)` was called with. The refusing
+/// function records it so the runtime can tell an exit from any other error.
+#[cfg(feature = "quickjs")]
+#[derive(Clone, Default)]
+pub struct SandboxExit(Rc<Cell<Option<i32>>>);
+
 #[cfg(feature = "quickjs")]
-pub fn register(ctx: Ctx<'_>, broker: BrokerHandle) -> Result<()> {
+impl SandboxExit {
+    /// The recorded code, if `exit` was called since the last take.
+    pub fn take(&self) -> Option<i32> {
+        self.0.take()
+    }
+}
+
+/// Registers all `__pneuma_private_ffi` host functions into the QuickJS context,
+/// with the ones `mode` excludes replaced by functions that throw. Scripts
+/// and `javascript:` URLs `policy` refuses throw before reaching the broker.
+/// Must be called BEFORE the ghost_shim.js is evaluated. Promises handed out by
+/// the async functions are tracked in `pending` for the runtime to settle, and
+/// a sandboxed `exit` records its code in `exit`.
+#[cfg(feature = "quickjs")]
+pub fn register<'js>(
+    ctx: Ctx<'js>,
+    broker: BrokerHandle,
+    mode: FfiMode,
+    policy: EvalPolicy,
+    pending: PendingCalls,
+    exit: SandboxExit,
+) -> Result<()> {
     let ffi = Object::new(ctx.clone())?;
+    let vetted = policy.has_allowlist();
+    let policy = Rc::new(policy);
 
     ffi.set(
         "print",
@@ -37,26 +217,195 @@ pub fn register(ctx: Ctx<'_>, broker: BrokerHandle) -> Result<()> {
         Function::new(ctx.clone(), move || -> Result<u32> { broker.create_page().map_err(to_js_err) })?
     })?;
 
+    // Takes a migration envelope as JSON, e.g. one produced by `extract_state`.
+    // The page is navigated to the envelope's URL, so it is vetted like one.
+    ffi.set("createPageWithState", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(ctx.clone(), move |envelope_json: String| -> Result<u32> {
+            let envelope: MigrationEnvelope = serde_json::from_str(&envelope_json)
+                .map_err(|e| to_js_err(anyhow::anyhow!("invalid state envelope: {e}")))?;
+            if let Some(url) = &envelope.current_url {
+                vet_navigate(&policy, url)?;
+            }
+            broker.create_page_with_state(envelope).map_err(to_js_err)
+        })?
+    })?;
+
     ffi.set("navigate", {
         let broker = broker.clone();
+        let policy = policy.clone();
         Function::new(
             ctx.clone(),
-            move |page_id: u32, url: String, opts_json: String| -> Result<String> {
-                broker.navigate(page_id, url, opts_json).map_err(to_js_err)
+            move |ctx: Ctx<'js>, page_id: u32, url: String, opts_json: String| -> Result<String> {
+                vet_navigate(&policy, &url)?;
+                broker.navigate(page_id, url, opts_json).map_err(|error| {
+                    match navigate_exception(&ctx, &error) {
+                        Ok(exception) => ctx.throw(exception.into_object().into_value()),
+                        Err(js_error) => js_error,
+                    }
+                })
             },
         )?
     })?;
 
     ffi.set("evaluate", {
         let broker = broker.clone();
+        let policy = policy.clone();
         Function::new(
             ctx.clone(),
             move |page_id: u32, script: String| -> Result<String> {
+                vet_script(&policy, &script)?;
                 broker.evaluate(page_id, script).map_err(to_js_err)
             },
         )?
     })?;
 
+    // Evaluates a script read host-side from under `PNEUMA_SCRIPT_ROOT`
+    // (required; unset refuses every path); paths escaping the root are refused.
+    ffi.set("evaluateFile", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(
+            ctx.clone(),
+            move |page_id: u32, path: String| -> Result<String> {
+                let script = ScriptRoot::from_env()
+                    .and_then(|root| root.read(&path))
+                    .map_err(to_js_err)?;
+                vet_script(&policy, &script)?;
+                broker.evaluate(page_id, script).map_err(to_js_err)
+            },
+        )?
+    })?;
+
+    // Promise-returning forms of `navigate` / `evaluate`: the broker call is
+    // queued and the JS thread stays free until the runtime settles it.
+    ffi.set("navigateAsync", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        let pending = pending.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, page_id: u32, url: String, opts_json: String| -> Result<Value<'js>> {
+                vet_navigate(&policy, &url)?;
+                let reply = broker.navigate_pending(page_id, url, opts_json).map_err(to_js_err)?;
+                pending.navigate_promise(&ctx, reply)
+            },
+        )?
+    })?;
+
+    ffi.set("evaluateAsync", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, page_id: u32, script: String| -> Result<Value<'js>> {
+                vet_script(&policy, &script)?;
+                let reply = broker.evaluate_pending(page_id, script).map_err(to_js_err)?;
+                pending.promise(&ctx, reply)
+            },
+        )?
+    })?;
+
+    // Same as `evaluate`, but hands JS the parsed result instead of JSON text.
+    ffi.set("evaluateJson", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, page_id: u32, script: String| -> Result<Value<'js>> {
+                vet_script(&policy, &script)?;
+                let raw = broker.evaluate(page_id, script).map_err(to_js_err)?;
+                ctx.json_parse(raw)
+            },
+        )?
+    })?;
+
+    // Large-result protocol: `evaluateStored` leaves the result in the broker
+    // and yields `{ handle, len }`; `readChunk` yields `{ data, nextOffset, done }`
+    // (offsets are UTF-8 byte offsets); `freeResult` releases the handle.
+    ffi.set("evaluateStored", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, page_id: u32, script: String| -> Result<Object<'js>> {
+                vet_script(&policy, &script)?;
+                let stored = broker.evaluate_stored(page_id, script).map_err(to_js_err)?;
+                let object = Object::new(ctx)?;
+                object.set("handle", stored.handle)?;
+                object.set("len", stored.len as f64)?;
+                Ok(object)
+            },
+        )?
+    })?;
+
+    ffi.set("readChunk", {
+        let broker = broker.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, handle: u32, offset: f64, len: f64| -> Result<Object<'js>> {
+                let chunk = broker
+                    .read_chunk(handle, offset as usize, len as usize)
+                    .map_err(to_js_err)?;
+                let object = Object::new(ctx)?;
+                object.set("data", chunk.data)?;
+                object.set("nextOffset", chunk.next_offset as f64)?;
+                object.set("done", chunk.done)?;
+                Ok(object)
+            },
+        )?
+    })?;
+
+    ffi.set("freeResult", {
+        let broker = broker.clone();
+        Function::new(ctx.clone(), move |handle: u32| -> Result<bool> {
+            broker.free_result(handle).map_err(to_js_err)
+        })?
+    })?;
+
+    // Runs on every open page; yields `[{ pageId, ok, value | error }]`.
+    // Pages are evaluated one at a time (see `BrokerRequest::EvaluateAll`),
+    // so the call takes as long as all of them together.
+    ffi.set("evaluateAll", {
+        let broker = broker.clone();
+        let policy = policy.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, script: String| -> Result<Value<'js>> {
+                vet_script(&policy, &script)?;
+                let results = broker.evaluate_all(script).map_err(to_js_err)?;
+                let entries: Vec<serde_json::Value> = results
+                    .into_iter()
+                    .map(|(page_id, result)| match result {
+                        Ok(raw) => serde_json::json!({
+                            "pageId": page_id,
+                            "ok": true,
+                            "value": serde_json::from_str::<serde_json::Value>(&raw)
+                                .unwrap_or(serde_json::Value::String(raw)),
+                        }),
+                        Err(error) => serde_json::json!({
+                            "pageId": page_id,
+                            "ok": false,
+                            "error": error.to_string(),
+                        }),
+                    })
+                    .collect();
+                ctx.json_parse(serde_json::Value::Array(entries).to_string())
+            },
+        )?
+    })?;
+
+    // Yields the page's cookies as `[{ name, value, domain?, path?, ... }]`.
+    ffi.set("getCookies", {
+        let broker = broker.clone();
+        Function::new(ctx.clone(), move |ctx: Ctx<'js>, page_id: u32| -> Result<Value<'js>> {
+            let cookies = broker.get_cookies(page_id).map_err(to_js_err)?;
+            let json = serde_json::to_string(&cookies).map_err(|e| to_js_err(e.into()))?;
+            ctx.json_parse(json)
+        })?
+    })?;
+
     ffi.set(
         "screenshot",
         Function::new(ctx.clone(), |page_id: u32| {
@@ -69,6 +418,18 @@ pub fn register(ctx: Ctx<'_>, broker: BrokerHandle) -> Result<()> {
         })?,
     )?;
 
+    // PNG bytes of one element, as an ArrayBuffer.
+    ffi.set("screenshotElement", {
+        let broker = broker.clone();
+        Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, page_id: u32, selector: String| -> Result<ArrayBuffer<'js>> {
+                let png = broker.screenshot_element(page_id, selector).map_err(to_js_err)?;
+                ArrayBuffer::new(ctx, png)
+            },
+        )?
+    })?;
+
     ffi.set(
         "closeBrowser",
         Function::new(ctx.clone(), || {
@@ -76,6 +437,8 @@ pub fn register(ctx: Ctx<'_>, broker: BrokerHandle) -> Result<()> {
         })?,
     )?;
 
+    // The explicit `()` return keeps never-type fallback out of the closure.
+    #[allow(clippy::unused_unit)]
     ffi.set(
         "exit",
         Function::new(ctx.clone(), |code: Option<i32>| -> () {
@@ -85,6 +448,30 @@ pub fn register(ctx: Ctx<'_>, broker: BrokerHandle) -> Result<()> {
         })?,
     )?;
 
+    if mode == FfiMode::Sandboxed {
+        let names = ffi.keys::<String>().collect::<Result<Vec<_>>>()?;
+        let kept = |name: &str| SANDBOX_ALLOWED.contains(&name) || (vetted && SANDBOX_VETTED.contains(&name));
+        for name in names.into_iter().filter(|name| !kept(name)) {
+            let refusal = if name == "exit" {
+                // Records the code, so the runtime can report how the script ended.
+                let exit = exit.clone();
+                Function::new(ctx.clone(), move |ctx: Ctx<'js>, code: Option<i32>| -> Result<()> {
+                    let code = code.unwrap_or(0);
+                    tracing::info!(target: "ghost_shim", exit_code = code, "ghost.exit() called in sandbox mode");
+                    exit.0.set(Some(code));
+                    Err(Exception::throw_message(&ctx, &format!("ffi.exit({code}){SANDBOX_REFUSAL}")))
+                })?
+            } else {
+                let message = format!("ffi.{name}(){SANDBOX_REFUSAL}");
+                Function::new(ctx.clone(), move |ctx: Ctx<'js>| -> Result<()> {
+                    tracing::warn!(target: "ghost_shim", error = %message, "refused host function");
+                    Err(Exception::throw_message(&ctx, &message))
+                })?
+            };
+            ffi.set(name.as_str(), refusal)?;
+        }
+    }
+
     ctx.globals().set("__pneuma_private_ffi", ffi)?;
 
     tracing::debug!(target: "pneuma_js", "FFI bridge registered");
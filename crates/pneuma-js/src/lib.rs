@@ -1,4 +1,8 @@
+pub mod eval_policy;
 pub mod ffi_bridge;
 pub mod runtime;
+pub mod script_root;
 
-pub use runtime::Runtime;
+pub use eval_policy::{EvalPolicy, EvalRejected};
+pub use runtime::{Completion, Runtime, RuntimeError, RuntimeLimits, RuntimeOptions};
+pub use script_root::ScriptRoot;
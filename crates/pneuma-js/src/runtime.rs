@@ -1,11 +1,15 @@
+use std::fmt;
+
 use anyhow::Result;
 use pneuma_broker::handle::BrokerHandle;
 
+use crate::eval_policy::EvalPolicy;
+use crate::ffi_bridge::{FfiMode, SANDBOX_REFUSAL};
 #[cfg(feature = "quickjs")]
-use crate::ffi_bridge;
+use crate::ffi_bridge::{self, PendingCalls, SandboxExit};
 
 #[cfg(feature = "quickjs")]
-use rquickjs::Runtime as QjsRuntime;
+use rquickjs::{CatchResultExt, CaughtError, Runtime as QjsRuntime};
 #[cfg(feature = "quickjs")]
 use std::sync::mpsc::{sync_channel, SyncSender};
 #[cfg(feature = "quickjs")]
@@ -20,17 +24,172 @@ const ASYNC_EXPR_SENTINEL: &str = "__PNEUMA_ASYNC_EXPR__";
 enum RuntimeCommand {
     Execute {
         source: String,
-        reply: SyncSender<Result<()>>,
+        reply: SyncSender<Result<Completion>>,
     },
     Eval {
         expr: String,
         reply: SyncSender<Result<String>>,
     },
+    MemoryUsage {
+        reply: SyncSender<usize>,
+    },
     Shutdown {
         reply: SyncSender<Result<()>>,
     },
 }
 
+/// Resource limits set on the QuickJS runtime when it is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeLimits {
+    /// Heap the runtime may allocate, shim included.
+    pub memory_bytes: usize,
+    /// Native stack the interpreter may use before raising a stack overflow.
+    pub max_stack_bytes: usize,
+}
+
+impl Default for RuntimeLimits {
+    fn default() -> Self {
+        Self {
+            memory_bytes: 256 * 1024 * 1024,
+            max_stack_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// How [`Runtime::with_options`] sets up a runtime. The default is the CLI's:
+/// every host function, default limits and every script allowed.
+#[derive(Debug, Clone)]
+pub struct RuntimeOptions {
+    pub mode: FfiMode,
+    pub limits: RuntimeLimits,
+    /// Checked before any script reaches a page.
+    pub eval_policy: EvalPolicy,
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            mode: FfiMode::Full,
+            limits: RuntimeLimits::default(),
+            eval_policy: EvalPolicy::default(),
+        }
+    }
+}
+
+/// How a script run by [`Runtime::run_script`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Completion {
+    /// Ran to the end, promises it started included.
+    Finished,
+    /// Called `ghost.exit(code)` in a sandboxed runtime, which throws rather
+    /// than ending the host process.
+    Exited { code: i32 },
+}
+
+/// A script ran into one of the [`RuntimeLimits`]. QuickJS unwinds the
+/// script instead of aborting, so the runtime stays usable afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeError {
+    MemoryLimitExceeded { limit_bytes: usize },
+    StackOverflow { limit_bytes: usize },
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MemoryLimitExceeded { limit_bytes } => {
+                write!(f, "script exceeded the JS memory limit of {limit_bytes} bytes")
+            }
+            Self::StackOverflow { limit_bytes } => {
+                write!(f, "script exceeded the JS stack limit of {limit_bytes} bytes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+#[cfg(feature = "quickjs")]
+impl RuntimeError {
+    /// The limit error a thrown QuickJS message stands for, if any. Stack
+    /// overflows read differently in QuickJS and QuickJS-NG.
+    fn from_message(message: &str, limits: &RuntimeLimits) -> Option<Self> {
+        if message.contains("out of memory") {
+            Some(Self::MemoryLimitExceeded {
+                limit_bytes: limits.memory_bytes,
+            })
+        } else if message.contains("stack overflow") || message.contains("Maximum call stack size exceeded") {
+            Some(Self::StackOverflow {
+                limit_bytes: limits.max_stack_bytes,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Turn a caught JS error into an [`anyhow::Error`], as a [`RuntimeError`]
+/// when it reports a breached limit. A thrown `null` becomes [`ThrownNull`]
+/// for [`memory_limit_error`] to look at once the context is released.
+#[cfg(feature = "quickjs")]
+fn script_error(error: CaughtError<'_>, limits: &RuntimeLimits) -> anyhow::Error {
+    let message = error.to_string();
+    if let CaughtError::Value(value) = &error {
+        if value.is_null() {
+            return ThrownNull(message).into();
+        }
+    }
+    match RuntimeError::from_message(&message, limits) {
+        Some(limit) => {
+            tracing::warn!(target: "pneuma_js", error = %message, "script hit a runtime limit");
+            limit.into()
+        }
+        None => anyhow::anyhow!("{message}"),
+    }
+}
+
+/// A script threw `null`. QuickJS does this itself when it runs out of memory
+/// before it can allocate the error object.
+#[cfg(feature = "quickjs")]
+#[derive(Debug)]
+struct ThrownNull(String);
+
+#[cfg(feature = "quickjs")]
+impl fmt::Display for ThrownNull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.trim_end())
+    }
+}
+
+#[cfg(feature = "quickjs")]
+impl std::error::Error for ThrownNull {}
+
+/// `error` as [`RuntimeError::MemoryLimitExceeded`] when it is a thrown `null`
+/// and the heap is at least half full. A failed allocation can be as large as
+/// what is already held (an array doubling its storage), so the heap need not
+/// be at the limit itself.
+#[cfg(feature = "quickjs")]
+fn memory_limit_error(error: anyhow::Error, runtime: &QjsRuntime, limits: &RuntimeLimits) -> anyhow::Error {
+    if !error.is::<ThrownNull>() {
+        return error;
+    }
+    let used = heap_used(runtime);
+    if used < limits.memory_bytes / 2 {
+        return error;
+    }
+    tracing::warn!(target: "pneuma_js", used_bytes = used, "script hit the memory limit");
+    RuntimeError::MemoryLimitExceeded {
+        limit_bytes: limits.memory_bytes,
+    }
+    .into()
+}
+
+/// Bytes the QuickJS heap currently holds.
+#[cfg(feature = "quickjs")]
+fn heap_used(runtime: &QjsRuntime) -> usize {
+    usize::try_from(runtime.memory_usage().malloc_size).unwrap_or(0)
+}
+
 pub struct Runtime {
     #[cfg(feature = "quickjs")]
     tx: SyncSender<RuntimeCommand>,
@@ -40,6 +199,31 @@ pub struct Runtime {
 
 impl Runtime {
     pub fn new(broker: BrokerHandle) -> Result<Self> {
+        Self::with_options(broker, RuntimeOptions::default())
+    }
+
+    /// Runtime for untrusted scripts: only page creation, navigation and
+    /// logging reach the host (see [`ffi_bridge::SANDBOX_ALLOWED`]); every
+    /// other host function, `ghost.exit()` included, raises a JS error. Run
+    /// scripts with [`run_script`](Self::run_script) to learn how they ended.
+    /// To let allowlisted scripts be evaluated, pass [`FfiMode::Sandboxed`]
+    /// and an [`EvalPolicy`] to [`with_options`](Self::with_options).
+    pub fn new_sandboxed(broker: BrokerHandle) -> Result<Self> {
+        Self::with_options(
+            broker,
+            RuntimeOptions {
+                mode: FfiMode::Sandboxed,
+                ..RuntimeOptions::default()
+            },
+        )
+    }
+
+    pub fn with_options(broker: BrokerHandle, options: RuntimeOptions) -> Result<Self> {
+        let RuntimeOptions {
+            mode,
+            limits,
+            eval_policy,
+        } = options;
         #[cfg(feature = "quickjs")]
         {
             let (cmd_tx, cmd_rx) = sync_channel::<RuntimeCommand>(0);
@@ -55,6 +239,8 @@ impl Runtime {
                             return;
                         }
                     };
+                    runtime.set_memory_limit(limits.memory_bytes);
+                    runtime.set_max_stack_size(limits.max_stack_bytes);
                     let context = match rquickjs::Context::full(&runtime) {
                         Ok(context) => context,
                         Err(error) => {
@@ -63,9 +249,11 @@ impl Runtime {
                         }
                     };
 
+                    let pending = PendingCalls::default();
+                    let exit = SandboxExit::default();
                     let init_result = context
                         .with(|ctx| -> rquickjs::Result<()> {
-                            ffi_bridge::register(ctx.clone(), broker)?;
+                            ffi_bridge::register(ctx.clone(), broker, mode, eval_policy, pending.clone(), exit.clone())?;
                             ctx.eval::<(), _>(GHOST_SHIM)?;
                             Ok(())
                         })
@@ -83,9 +271,25 @@ impl Runtime {
                     while let Ok(command) = cmd_rx.recv() {
                         match command {
                             RuntimeCommand::Execute { source, reply } => {
+                                // Caught inside the context so the thrown message
+                                // (e.g. a sandbox refusal) reaches the caller.
+                                exit.take();
                                 let result = context
-                                    .with(|ctx| ctx.eval::<(), _>(source.as_str()))
-                                    .map_err(anyhow::Error::from);
+                                    .with(|ctx| {
+                                        ctx.eval::<(), _>(source.as_str())
+                                            .catch(&ctx)
+                                            .map_err(|error| script_error(error, &limits))
+                                    })
+                                    .and_then(|()| run_event_loop(&runtime, &context, &pending, &limits))
+                                    .map_err(|error| memory_limit_error(error, &runtime, &limits));
+                                if result.is_err() {
+                                    pending.clear();
+                                }
+                                // An error after a sandboxed `exit` is that exit unwinding.
+                                let result = match (result, exit.take()) {
+                                    (Err(_), Some(code)) => Ok(Completion::Exited { code }),
+                                    (result, _) => result.map(|()| Completion::Finished),
+                                };
                                 let _ = reply.send(result);
                             }
                             RuntimeCommand::Eval { expr, reply } => {
@@ -104,8 +308,12 @@ impl Runtime {
                                     }})()"
                                 );
                                 let result = context
-                                    .with(|ctx| ctx.eval::<String, _>(wrapped.as_str()))
-                                    .map_err(anyhow::Error::from)
+                                    .with(|ctx| {
+                                        ctx.eval::<String, _>(wrapped.as_str())
+                                            .catch(&ctx)
+                                            .map_err(|error| script_error(error, &limits))
+                                    })
+                                    .map_err(|error| memory_limit_error(error, &runtime, &limits))
                                     .and_then(|rendered| {
                                         if rendered == ASYNC_EXPR_SENTINEL {
                                             anyhow::bail!("async expressions are not supported yet");
@@ -114,6 +322,9 @@ impl Runtime {
                                     });
                                 let _ = reply.send(result);
                             }
+                            RuntimeCommand::MemoryUsage { reply } => {
+                                let _ = reply.send(heap_used(&runtime));
+                            }
                             RuntimeCommand::Shutdown { reply } => {
                                 tracing::info!(target: "pneuma_js", "QuickJS thread shutting down");
                                 let _ = reply.send(Ok(()));
@@ -122,10 +333,11 @@ impl Runtime {
                         }
                     }
 
+                    pending.clear();
                     tracing::info!(target: "pneuma_js", "QuickJS thread exited");
                 })?;
 
-            return match init_rx.recv() {
+            match init_rx.recv() {
                 Ok(Ok(())) => {
                     tracing::info!(target: "pneuma_js", "Runtime initialized");
                     Ok(Self {
@@ -141,12 +353,12 @@ impl Runtime {
                     let _ = thread.join();
                     Err(anyhow::anyhow!("QuickJS thread exited before signaling init"))
                 }
-            };
+            }
         }
 
         #[cfg(not(feature = "quickjs"))]
         {
-            let _ = broker;
+            let _ = (broker, mode, limits, eval_policy);
             Ok(Self {})
         }
     }
@@ -164,6 +376,16 @@ impl Runtime {
     }
 
     pub fn execute_script(&self, source: &str) -> Result<()> {
+        match self.run_script(source)? {
+            Completion::Finished => Ok(()),
+            Completion::Exited { code } => anyhow::bail!("ffi.exit({code}){SANDBOX_REFUSAL}"),
+        }
+    }
+
+    /// Like [`execute_script`](Self::execute_script), but a sandboxed
+    /// `ghost.exit(code)` that ends the script ends the run with
+    /// [`Completion::Exited`] instead of an error.
+    pub fn run_script(&self, source: &str) -> Result<Completion> {
         #[cfg(feature = "quickjs")]
         {
             let (reply_tx, reply_rx) = sync_channel(0);
@@ -173,9 +395,9 @@ impl Runtime {
                     reply: reply_tx,
                 })
                 .map_err(|_| anyhow::anyhow!("QuickJS thread has exited"))?;
-            return reply_rx
+            reply_rx
                 .recv()
-                .map_err(|_| anyhow::anyhow!("QuickJS thread dropped reply"))?;
+                .map_err(|_| anyhow::anyhow!("QuickJS thread dropped reply"))?
         }
 
         #[cfg(not(feature = "quickjs"))]
@@ -185,6 +407,26 @@ impl Runtime {
         }
     }
 
+    /// Bytes the JS heap currently holds, shim included; compare with
+    /// [`RuntimeLimits::memory_bytes`].
+    pub fn memory_usage(&self) -> Result<usize> {
+        #[cfg(feature = "quickjs")]
+        {
+            let (reply_tx, reply_rx) = sync_channel(0);
+            self.tx
+                .send(RuntimeCommand::MemoryUsage { reply: reply_tx })
+                .map_err(|_| anyhow::anyhow!("QuickJS thread has exited"))?;
+            reply_rx
+                .recv()
+                .map_err(|_| anyhow::anyhow!("QuickJS thread dropped reply"))
+        }
+
+        #[cfg(not(feature = "quickjs"))]
+        {
+            anyhow::bail!("pneuma-js was built without `quickjs` support")
+        }
+    }
+
     pub fn eval_expression(&self, expression: &str) -> Result<String> {
         #[cfg(feature = "quickjs")]
         {
@@ -195,9 +437,9 @@ impl Runtime {
                     reply: reply_tx,
                 })
                 .map_err(|_| anyhow::anyhow!("QuickJS thread has exited"))?;
-            return reply_rx
+            reply_rx
                 .recv()
-                .map_err(|_| anyhow::anyhow!("QuickJS thread dropped reply"))?;
+                .map_err(|_| anyhow::anyhow!("QuickJS thread dropped reply"))?
         }
 
         #[cfg(not(feature = "quickjs"))]
@@ -208,6 +450,33 @@ impl Runtime {
     }
 }
 
+/// Run promise jobs and settle async FFI calls until the script has nothing
+/// left in flight.
+#[cfg(feature = "quickjs")]
+fn run_event_loop(
+    runtime: &QjsRuntime,
+    context: &rquickjs::Context,
+    pending: &PendingCalls,
+    limits: &RuntimeLimits,
+) -> Result<()> {
+    loop {
+        while runtime.is_job_pending() {
+            runtime
+                .execute_pending_job()
+                .map_err(|_| anyhow::anyhow!("a pending JavaScript job threw"))?;
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        context.with(|ctx| {
+            pending
+                .settle(&ctx)
+                .catch(&ctx)
+                .map_err(|error| script_error(error, limits))
+        })?;
+    }
+}
+
 impl Drop for Runtime {
     fn drop(&mut self) {
         #[cfg(feature = "quickjs")]
@@ -221,3 +490,233 @@ impl Drop for Runtime {
         }
     }
 }
+
+#[cfg(all(test, feature = "quickjs"))]
+mod tests {
+    use super::{Completion, Runtime, RuntimeError, RuntimeLimits, RuntimeOptions};
+    use crate::eval_policy::EvalPolicy;
+    use crate::ffi_bridge::FfiMode;
+    use pneuma_broker::handle::{BrokerHandle, BrokerRequest};
+    use pneuma_broker::policy::NavigationBlocked;
+
+    #[test]
+    fn allocating_past_the_memory_limit_fails_gracefully() {
+        // The limit sits a fixed margin above what the shim itself takes, so
+        // the test does not depend on the shim's size.
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let baseline = Runtime::new(BrokerHandle::new(tx.clone()))
+            .and_then(|runtime| runtime.memory_usage())
+            .expect("runtime should start");
+        let limit_bytes = baseline + 4 * 1024 * 1024;
+        let limits = RuntimeLimits {
+            memory_bytes: limit_bytes,
+            ..RuntimeLimits::default()
+        };
+        let options = RuntimeOptions {
+            limits,
+            ..RuntimeOptions::default()
+        };
+        let runtime = Runtime::with_options(BrokerHandle::new(tx), options).expect("runtime should start");
+        let limit_hit = Some(RuntimeError::MemoryLimitExceeded { limit_bytes });
+
+        // Whole-MiB buffers stop about 1 MiB short of the limit, which leaves
+        // room to free them afterwards.
+        let error = runtime
+            .execute_script("globalThis.hog = []; for (;;) { hog.push(new ArrayBuffer(1024 * 1024)); }")
+            .expect_err("the allocation should hit the limit");
+        assert_eq!(error.downcast_ref::<RuntimeError>(), limit_hit.as_ref(), "{error:?}");
+        runtime.execute_script("globalThis.hog = null;").unwrap();
+        let error = runtime.execute_script("throw null;").expect_err("throw null should fail");
+        assert!(error.downcast_ref::<RuntimeError>().is_none(), "a script's own null is not a limit: {error:?}");
+        assert_eq!(runtime.eval_expression("1 + 1").unwrap(), "2", "the runtime survives the breach");
+
+        let error = runtime
+            .execute_script("function down(n) { return down(n + 1) + 1; } down(0);")
+            .expect_err("unbounded recursion should hit the stack limit");
+        assert!(matches!(error.downcast_ref::<RuntimeError>(), Some(RuntimeError::StackOverflow { .. })), "{error:?}");
+
+    }
+
+    #[test]
+    fn sandboxed_exit_throws_instead_of_exiting() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let runtime = Runtime::new_sandboxed(BrokerHandle::new(tx)).expect("sandboxed runtime should start");
+
+        let error = runtime.execute_script("ghost.exit(3);").expect_err("ghost.exit() should throw");
+        assert!(error.to_string().contains("not available in sandbox mode"), "{error:?}");
+        assert_eq!(runtime.run_script("ghost.exit(3);").unwrap(), Completion::Exited { code: 3 });
+        assert_eq!(runtime.run_script("console.log('done');").unwrap(), Completion::Finished);
+        // Only a real `exit` call counts; a look-alike error stays an error.
+        assert!(runtime
+            .run_script("throw new Error('ffi.exit(0) is not available in sandbox mode');")
+            .is_err());
+        assert_eq!(
+            runtime
+                .eval_expression("(() => { try { ghost.exit(); } catch (e) { return e instanceof Error; } })()")
+                .unwrap(),
+            "true"
+        );
+        assert!(runtime.execute_script("__pneuma_private_ffi.print('hi');").is_err());
+        assert_eq!(runtime.eval_expression("typeof ghost.open").unwrap(), "\"function\"");
+
+        // Without an allowlist only navigation gets through; evaluate never
+        // reaches the broker.
+        let error = runtime
+            .run_script("__pneuma_private_ffi.evaluate(1, 'document.title');")
+            .expect_err("evaluate is not on the allowlist");
+        assert!(error.to_string().contains("ffi.evaluate() is not available"), "{error:?}");
+        assert!(rx.try_recv().is_err(), "nothing reached the broker");
+    }
+
+    #[test]
+    fn sandboxed_runtime_evaluates_only_allowlisted_scripts() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Evaluate { reply, .. } = req {
+                    let _ = reply.send(Ok("Example Domain".into()));
+                }
+            }
+        });
+        let options = RuntimeOptions {
+            mode: FfiMode::Sandboxed,
+            eval_policy: EvalPolicy::default().allow_script("document.title"),
+            ..RuntimeOptions::default()
+        };
+        let runtime = Runtime::with_options(BrokerHandle::new(tx), options).expect("runtime should start");
+
+        runtime
+            .execute_script(
+                "if (__pneuma_private_ffi.evaluate(1, 'document.title') !== 'Example Domain') \
+                 throw new Error('unexpected result');",
+            )
+            .expect("an allowlisted script should reach the page");
+        let error = runtime
+            .run_script("__pneuma_private_ffi.evaluate(1, 'document.cookie');")
+            .expect_err("a script off the allowlist should throw");
+        assert!(error.to_string().contains("not on the evaluate allowlist"), "{error:?}");
+        assert!(runtime.run_script("__pneuma_private_ffi.evaluateFile(1, 'x.js');").is_err());
+        assert_eq!(runtime.run_script("ghost.exit(2);").unwrap(), Completion::Exited { code: 2 });
+    }
+
+    #[test]
+    fn eval_policy_refuses_scripts_before_they_reach_the_broker() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let options = RuntimeOptions {
+            eval_policy: EvalPolicy::default().deny_pattern("while(true)"),
+            ..RuntimeOptions::default()
+        };
+        let runtime = Runtime::with_options(BrokerHandle::new(tx), options).expect("runtime should start");
+
+        let error = runtime
+            .execute_script("__pneuma_private_ffi.evaluate(1, 'while (true) {}');")
+            .expect_err("a denied script should throw");
+        assert!(error.to_string().contains("denied pattern `while(true)`"), "{error:?}");
+        assert!(
+            runtime
+                .execute_script("__pneuma_private_ffi.navigate(1, 'javascript:while(true){}', '{}');")
+                .is_err()
+        );
+        let envelope = r#"{"source_engine":"servo","captured_at_ms":0,"current_url":"javascript:while(true){}",
+            "cookies":[],"local_storage":[]}"#;
+        let error = runtime
+            .execute_script(&format!("__pneuma_private_ffi.createPageWithState({envelope:?});"))
+            .expect_err("a denied restore URL should throw");
+        assert!(error.to_string().contains("denied pattern `while(true)`"), "{error:?}");
+        assert!(rx.try_recv().is_err(), "nothing reached the broker");
+    }
+
+    #[test]
+    fn ghost_navigate_returns_a_promise_settled_by_the_broker() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Navigate { url, reply, .. } = req {
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    let result = if url.contains("broken") {
+                        Err(anyhow::anyhow!("engine unavailable"))
+                    } else {
+                        Ok(r#"{"ok":true,"title":"Example Domain"}"#.to_string())
+                    };
+                    let _ = reply.send(result);
+                }
+            }
+        });
+        let runtime = Runtime::new(BrokerHandle::new(tx)).expect("runtime should start");
+
+        runtime
+            .execute_script(
+                r#"
+                globalThis.order = [];
+                (async () => {
+                    const loading = ghost.navigate(1, "https://example.com/");
+                    order.push("queued");
+                    const meta = await loading;
+                    order.push(meta.title);
+                    try {
+                        await ghost.navigate(1, "https://broken.example/");
+                    } catch (error) {
+                        order.push(error.message);
+                    }
+                })();
+                "#,
+            )
+            .expect("script should run to completion");
+        assert_eq!(
+            runtime.eval_expression("order").unwrap(),
+            r#"["queued","Example Domain","engine unavailable"]"#
+        );
+    }
+
+    #[test]
+    fn failed_navigates_throw_errors_with_kind_and_retryable() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Some(req) = rx.blocking_recv() {
+                if let BrokerRequest::Navigate { url, reply, .. } = req {
+                    let _ = reply.send(Err(NavigationBlocked {
+                        url,
+                        reason: "denylisted".into(),
+                    }
+                    .into()));
+                }
+            }
+        });
+        let runtime = Runtime::new(BrokerHandle::new(tx)).expect("runtime should start");
+
+        runtime
+            .execute_script(
+                r#"
+                globalThis.caught = [];
+                try {
+                    __pneuma_private_ffi.navigate(1, "https://ads.example/", "{}");
+                } catch (error) {
+                    caught.push([error.kind, error.retryable]);
+                }
+                (async () => {
+                    try {
+                        await ghost.navigate(1, "https://ads.example/");
+                    } catch (error) {
+                        caught.push([error.kind, error.retryable, error instanceof Error]);
+                    }
+                })();
+                "#,
+            )
+            .expect("script should run to completion");
+        assert_eq!(
+            runtime.eval_expression("caught").unwrap(),
+            r#"[["policy_blocked",false],["policy_blocked",false,true]]"#
+        );
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        drop(rx);
+        let runtime = Runtime::new(BrokerHandle::new(tx)).expect("runtime should start");
+        let kind = runtime
+            .eval_expression(
+                r#"(() => { try { __pneuma_private_ffi.navigate(1, "https://example.com/", "{}"); }
+                    catch (error) { return error.kind; } })()"#,
+            )
+            .unwrap();
+        assert_eq!(kind, r#""channel_closed""#);
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+/// Directory that `ffi.evaluateFile` may read scripts from. Paths are
+/// resolved against it, and anything that lands outside it after `..` and
+/// symlinks are resolved is refused.
+///
+/// Configured by `PNEUMA_SCRIPT_ROOT`, which must be set: there is no default,
+/// so a script cannot read files just because they sit under the working
+/// directory.
+#[derive(Debug, Clone)]
+pub struct ScriptRoot {
+    root: PathBuf,
+}
+
+impl ScriptRoot {
+    pub fn new(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("script root {} is not accessible", root.display()))?;
+        Ok(Self { root })
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Self::from_setting(std::env::var("PNEUMA_SCRIPT_ROOT").ok().as_deref())
+    }
+
+    fn from_setting(setting: Option<&str>) -> Result<Self> {
+        match setting.map(str::trim) {
+            Some(root) if !root.is_empty() => Self::new(root),
+            _ => bail!("no script root: set PNEUMA_SCRIPT_ROOT to the directory evaluateFile may read from"),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Canonical path of `path` (relative to the root, or absolute), provided
+    /// it exists and stays inside the root.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let joined = self.root.join(path);
+        let resolved = joined
+            .canonicalize()
+            .with_context(|| format!("script file {path} not found under {}", self.root.display()))?;
+        if !resolved.starts_with(&self.root) {
+            bail!("script file {path} is outside the script root {}", self.root.display());
+        }
+        Ok(resolved)
+    }
+
+    pub fn read(&self, path: &str) -> Result<String> {
+        let resolved = self.resolve(path)?;
+        std::fs::read_to_string(&resolved).with_context(|| format!("failed to read script file {}", resolved.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScriptRoot;
+
+    #[test]
+    fn reads_in_root_paths_and_rejects_traversal() {
+        let base = std::env::temp_dir().join(format!("pneuma-script-root-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("scripts");
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("lib/helper.js"), "1 + 1").unwrap();
+        std::fs::write(base.join("secret.js"), "'secret'").unwrap();
+
+        let scripts = ScriptRoot::new(&root).unwrap();
+        assert_eq!(scripts.read("lib/helper.js").unwrap(), "1 + 1");
+        assert_eq!(scripts.read("./lib/../lib/helper.js").unwrap(), "1 + 1");
+
+        let error = scripts.read("../secret.js").unwrap_err();
+        assert!(error.to_string().contains("outside the script root"), "{error:?}");
+        let absolute = base.join("secret.js");
+        assert!(scripts.read(absolute.to_str().unwrap()).is_err());
+        assert!(scripts.read("missing.js").is_err());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn the_root_must_be_configured() {
+        for setting in [None, Some(""), Some("  ")] {
+            let error = ScriptRoot::from_setting(setting).unwrap_err();
+            assert!(error.to_string().contains("PNEUMA_SCRIPT_ROOT"), "{error:?}");
+        }
+        let root = ScriptRoot::from_setting(Some(std::env::temp_dir().to_str().unwrap())).unwrap();
+        assert_eq!(root.root(), std::env::temp_dir().canonicalize().unwrap());
+    }
+}
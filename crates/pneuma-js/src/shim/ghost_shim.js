@@ -37,16 +37,24 @@
     }
 
     async goto(url, options = {}) {
-      const raw = ffi.navigate(this._id, url, JSON.stringify(options));
-      const meta = JSON.parse(raw);
-      if (meta.error) throw new Error(`Navigation failed: ${meta.error}`);
-      return meta;
+      return ghost.navigate(this._id, url, options);
     }
 
     async evaluate(fn, ...args) {
       const script = `(${fn.toString()})(${args.map(JSON.stringify).join(",")})`;
-      const raw = ffi.evaluate(this._id, script);
-      return JSON.parse(raw);
+      return ghost.evaluate(this._id, script);
+    }
+
+    // Runs a script file from the host's script root; `path` is relative to it.
+    async evaluateFile(path) {
+      return JSON.parse(ffi.evaluateFile(this._id, path));
+    }
+
+    // Like `evaluate`, but leaves the result in the broker and returns a
+    // handle for `ghost.getLargeResult`. Use for multi-megabyte results.
+    async evaluateLarge(fn, ...args) {
+      const script = `(${fn.toString()})(${args.map(JSON.stringify).join(",")})`;
+      return ffi.evaluateStored(this._id, script).handle;
     }
 
     async $(selector) {
@@ -61,6 +69,15 @@
       return ffi.screenshot(this._id);
     }
 
+    async screenshotElement(selector) {
+      return ffi.screenshotElement(this._id, selector);
+    }
+
+    // Cookies of the current document, HttpOnly ones included.
+    async cookies() {
+      return ffi.getCookies(this._id);
+    }
+
     async title() {
       return this.evaluate(() => document.title);
     }
@@ -76,6 +93,19 @@
       return new Page(id);
     }
 
+    async newPageWithState(state) {
+      const json = typeof state === "string" ? state : JSON.stringify(state);
+      const id = ffi.createPageWithState(json);
+      return new Page(id);
+    }
+
+    // Runs on every open page in turn, not concurrently; resolves to
+    // `[{ pageId, ok, value | error }]`.
+    async evaluateAll(fn, ...args) {
+      const script = `(${fn.toString()})(${args.map(JSON.stringify).join(",")})`;
+      return ffi.evaluateAll(script);
+    }
+
     async close() {
       ffi.closeBrowser();
     }
@@ -95,6 +125,37 @@
       return page;
     },
 
+    // Reads a handle from `page.evaluateLarge` in chunks and frees it.
+    getLargeResult: async (handle, options = {}) => {
+      const chunkSize = options.chunkSize ?? 1024 * 1024;
+      const chunks = [];
+      try {
+        let offset = 0;
+        for (;;) {
+          const chunk = ffi.readChunk(handle, offset, chunkSize);
+          chunks.push(chunk.data);
+          if (chunk.done) break;
+          offset = chunk.nextOffset;
+        }
+      } finally {
+        ffi.freeResult(handle);
+      }
+      return JSON.parse(chunks.join(""));
+    },
+
+    // Promise-based broker calls; the script keeps running while they are
+    // in flight, so several pages can load at once.
+    navigate: async (pageId, url, options = {}) => {
+      const raw = await ffi.navigateAsync(pageId, url, JSON.stringify(options));
+      const meta = JSON.parse(raw);
+      if (meta.error) throw new Error(`Navigation failed: ${meta.error}`);
+      return meta;
+    },
+
+    evaluate: async (pageId, script) => {
+      return JSON.parse(await ffi.evaluateAsync(pageId, script));
+    },
+
     exit: (code = 0) => ffi.exit(code),
   };
 
@@ -0,0 +1,200 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cookie_store::{CookieDomain, CookieExpiration, RawCookie};
+use pneuma_engines::MigrationCookie;
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+
+/// Cookie store shared between the interceptor's reqwest client and the
+/// engine cookie bridge, so cookies can be read out in full (domain, path,
+/// flags) rather than only as a request header.
+#[derive(Debug, Clone, Default)]
+pub struct SharedCookieStore {
+    inner: Arc<Mutex<cookie_store::CookieStore>>,
+}
+
+impl SharedCookieStore {
+    fn lock(&self) -> MutexGuard<'_, cookie_store::CookieStore> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Every unexpired cookie, in the shape engines import and export.
+    pub fn export(&self) -> Vec<MigrationCookie> {
+        self.lock().iter_unexpired().map(to_migration_cookie).collect()
+    }
+
+    /// Store `cookies` as if each had been set by its own domain and path.
+    /// Returns how many were accepted; cookies without a domain, or that the
+    /// store rejects (e.g. a public-suffix domain), are skipped.
+    pub fn import(&self, cookies: &[MigrationCookie]) -> usize {
+        let mut store = self.lock();
+        cookies
+            .iter()
+            .filter(|cookie| {
+                let Some((set_cookie, url)) = to_set_cookie(cookie) else {
+                    return false;
+                };
+                store.parse(&set_cookie, &url).is_ok()
+            })
+            .count()
+    }
+}
+
+impl reqwest::cookie::CookieStore for SharedCookieStore {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|header| RawCookie::parse(header.to_string()).ok());
+        self.lock().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .lock()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// One `Set-Cookie` header as received from `url`, with the domain and path
+/// defaults the store applies. `None` when it is malformed or `url` may not
+/// set it.
+pub(crate) fn parse_set_cookie(header: &str, url: &Url) -> Option<MigrationCookie> {
+    let cookie = cookie_store::Cookie::parse(header.to_string(), url).ok()?;
+    Some(to_migration_cookie(&cookie))
+}
+
+/// Host-only cookies keep their bare host; domain cookies get the leading dot
+/// WebDriver uses for them.
+fn to_migration_cookie(cookie: &cookie_store::Cookie<'static>) -> MigrationCookie {
+    let domain = match &cookie.domain {
+        CookieDomain::HostOnly(host) => Some(host.clone()),
+        CookieDomain::Suffix(suffix) => Some(format!(".{suffix}")),
+        CookieDomain::NotPresent | CookieDomain::Empty => None,
+    };
+    let expiry = match &cookie.expires {
+        CookieExpiration::AtUtc(at) => u64::try_from(at.unix_timestamp()).ok(),
+        CookieExpiration::SessionEnd => None,
+    };
+    MigrationCookie {
+        name: cookie.name().to_string(),
+        value: cookie.value().to_string(),
+        domain,
+        path: Some(String::from(&cookie.path)),
+        secure: cookie.secure(),
+        http_only: cookie.http_only(),
+        expiry,
+        same_site: cookie.same_site().map(|same_site| same_site.to_string()),
+    }
+}
+
+/// A `Set-Cookie` line and the URL it is treated as coming from.
+fn to_set_cookie(cookie: &MigrationCookie) -> Option<(String, Url)> {
+    let domain = cookie.domain.as_deref()?;
+    let host = domain.trim_start_matches('.');
+    let path = cookie.path.as_deref().unwrap_or("/");
+    let secure = cookie.secure.unwrap_or(false);
+    let scheme = if secure { "https" } else { "http" };
+    let url = Url::parse(&format!("{scheme}://{host}{path}")).ok()?;
+
+    let mut set_cookie = format!("{}={}; Path={path}", cookie.name, cookie.value);
+    if domain.starts_with('.') {
+        set_cookie.push_str(&format!("; Domain={host}"));
+    }
+    if secure {
+        set_cookie.push_str("; Secure");
+    }
+    if cookie.http_only.unwrap_or(false) {
+        set_cookie.push_str("; HttpOnly");
+    }
+    if let Some(same_site) = &cookie.same_site {
+        set_cookie.push_str(&format!("; SameSite={same_site}"));
+    }
+    if let Some(expiry) = cookie.expiry {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+        set_cookie.push_str(&format!("; Max-Age={}", expiry.saturating_sub(now)));
+    }
+    Some((set_cookie, url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedCookieStore;
+    use pneuma_engines::MigrationCookie;
+    use reqwest::cookie::CookieStore;
+    use reqwest::header::HeaderValue;
+    use reqwest::Url;
+
+    fn by_name<'a>(cookies: &'a [MigrationCookie], name: &str) -> &'a MigrationCookie {
+        cookies.iter().find(|cookie| cookie.name == name).expect("cookie should be exported")
+    }
+
+    #[test]
+    fn exports_response_cookies_with_domain_and_path() {
+        let store = SharedCookieStore::default();
+        let url = Url::parse("https://shop.example.com/cart/view").unwrap();
+        let headers = [
+            HeaderValue::from_static("session=abc; Path=/; Secure; HttpOnly; SameSite=Lax"),
+            HeaderValue::from_static("pref=dark; Domain=example.com; Path=/account; Max-Age=3600"),
+            HeaderValue::from_static("cart=3"),
+        ];
+        store.set_cookies(&mut headers.iter(), &url);
+
+        let cookies = store.export();
+        assert_eq!(cookies.len(), 3);
+
+        let session = by_name(&cookies, "session");
+        assert_eq!(session.value, "abc");
+        assert_eq!(session.domain.as_deref(), Some("shop.example.com"));
+        assert_eq!(session.path.as_deref(), Some("/"));
+        assert_eq!((session.secure, session.http_only), (Some(true), Some(true)));
+        assert_eq!(session.same_site.as_deref(), Some("Lax"));
+        assert_eq!(session.expiry, None);
+
+        let pref = by_name(&cookies, "pref");
+        assert_eq!(pref.domain.as_deref(), Some(".example.com"));
+        assert_eq!(pref.path.as_deref(), Some("/account"));
+        assert!(pref.expiry.is_some());
+
+        // No Path attribute: the default path is the request path's directory.
+        assert_eq!(by_name(&cookies, "cart").path.as_deref(), Some("/cart"));
+    }
+
+    #[test]
+    fn imported_cookies_are_sent_on_matching_requests() {
+        let exported = {
+            let source = SharedCookieStore::default();
+            let url = Url::parse("https://shop.example.com/").unwrap();
+            let headers = [
+                HeaderValue::from_static("session=abc; Path=/"),
+                HeaderValue::from_static("pref=dark; Domain=example.com; Path=/account"),
+            ];
+            source.set_cookies(&mut headers.iter(), &url);
+            source.export()
+        };
+
+        let store = SharedCookieStore::default();
+        assert_eq!(store.import(&exported), 2);
+        let header = |url: &str| {
+            store
+                .cookies(&Url::parse(url).unwrap())
+                .map(|value| value.to_str().unwrap().to_string())
+        };
+        assert_eq!(header("https://shop.example.com/").as_deref(), Some("session=abc"));
+        assert_eq!(header("https://www.example.com/account/me").as_deref(), Some("pref=dark"));
+        assert_eq!(header("https://other.example.org/"), None);
+
+        let domainless = MigrationCookie {
+            domain: None,
+            ..exported[0].clone()
+        };
+        assert_eq!(store.import(&[domainless]), 0);
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use pneuma_engines::MigrationCookie;
+
 #[derive(Debug, Default, Clone)]
 pub struct SessionCookieJar {
     cookies: HashMap<String, String>,
@@ -10,6 +12,14 @@ impl SessionCookieJar {
         self.cookies.insert(name.into(), value.into());
     }
 
+    /// Record each cookie's name and value; a later cookie with the same
+    /// name wins.
+    pub fn insert_cookies(&mut self, cookies: &[MigrationCookie]) {
+        for cookie in cookies {
+            self.insert(cookie.name.clone(), cookie.value.clone());
+        }
+    }
+
     pub fn get(&self, name: &str) -> Option<&str> {
         self.cookies.get(name).map(String::as_str)
     }
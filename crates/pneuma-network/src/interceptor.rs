@@ -1,26 +1,460 @@
-use anyhow::Result;
-use reqwest::Client;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use anyhow::{bail, Context, Result};
+use pneuma_engines::{HeadlessEngine, MigrationCookie, MigrationEnvelope};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, LOCATION, SET_COOKIE, USER_AGENT};
+use reqwest::{redirect, Client, Response, Url};
+use tokio::sync::Semaphore;
+
+use crate::cookie_bridge::{parse_set_cookie, SharedCookieStore};
+use crate::cookie_jar::SessionCookieJar;
+use crate::rate_limit::RateLimiter;
 use crate::stealth::identity::BrowserIdentity;
 
+/// Redirects [`NetworkInterceptor::get`] follows before giving up, as many as
+/// reqwest's default policy.
+const MAX_REDIRECTS: usize = 10;
+
 #[derive(Debug, Clone)]
 pub struct NetworkInterceptor {
     client: Client,
     identity: BrowserIdentity,
+    cookies: SharedCookieStore,
+    /// Caps requests in flight across all hosts; `None` is unlimited.
+    concurrency: Option<Arc<Semaphore>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+/// A fetched page and the cookies its response set.
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    /// Status of the final response, after redirects.
+    pub status: u16,
+    /// URL the final response came from.
+    pub url: String,
+    /// The `Set-Cookie` headers of every response, redirects included, each
+    /// parsed against the URL that sent it; ones it may not set are dropped.
+    /// The client's own store keeps them as well.
+    pub cookies: Vec<MigrationCookie>,
+    pub body: String,
+}
+
+impl FetchResponse {
+    /// Name/value view of [`Self::cookies`].
+    pub fn cookie_jar(&self) -> SessionCookieJar {
+        let mut jar = SessionCookieJar::default();
+        jar.insert_cookies(&self.cookies);
+        jar
+    }
+}
+
+/// Configures a [`NetworkInterceptor`]; by default requests are neither
+/// capped nor throttled. Clones of the built interceptor share its limits.
+#[derive(Debug)]
+pub struct NetworkInterceptorBuilder {
+    identity: BrowserIdentity,
+    max_concurrent_requests: Option<usize>,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl NetworkInterceptorBuilder {
+    /// At most `max` requests in flight at once; `0` is treated as 1.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max.max(1));
+        self
+    }
+
+    /// Per host, allow `burst` requests at once and then `per_second` on
+    /// average. Panics unless `per_second` is positive.
+    pub fn per_host_rate(mut self, per_second: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(per_second, burst));
+        self
+    }
+
+    pub fn build(self) -> Result<NetworkInterceptor> {
+        let cookies = SharedCookieStore::default();
+        let client = Client::builder()
+            .cookie_provider(Arc::new(cookies.clone()))
+            // `get` follows redirects itself to see every hop's cookies.
+            .redirect(redirect::Policy::none())
+            .default_headers(default_headers(&self.identity)?)
+            .build()?;
+        Ok(NetworkInterceptor {
+            client,
+            identity: self.identity,
+            cookies,
+            concurrency: self.max_concurrent_requests.map(|max| Arc::new(Semaphore::new(max))),
+            rate_limiter: self.rate_limiter.map(Arc::new),
+        })
+    }
 }
 
 impl NetworkInterceptor {
+    /// Interceptor without concurrency or rate limits.
     pub fn new(identity: BrowserIdentity) -> Result<Self> {
-        let client = Client::builder().cookie_store(true).build()?;
-        Ok(Self { client, identity })
+        Self::builder(identity).build()
+    }
+
+    pub fn builder(identity: BrowserIdentity) -> NetworkInterceptorBuilder {
+        NetworkInterceptorBuilder {
+            identity,
+            max_concurrent_requests: None,
+            rate_limiter: None,
+        }
     }
 
     pub fn identity(&self) -> &BrowserIdentity {
         &self.identity
     }
 
+    /// Cookies the client currently holds.
+    pub fn export_cookies(&self) -> Vec<MigrationCookie> {
+        self.cookies.export()
+    }
+
+    /// Add `cookies` to the client's store; returns how many were accepted.
+    pub fn import_cookies(&self, cookies: &[MigrationCookie]) -> usize {
+        self.cookies.import(cookies)
+    }
+
+    /// Copy the client's cookies into `engine`'s session (cookies only; no
+    /// localStorage). Cookie sync is opt-in: nothing calls this implicitly.
+    ///
+    /// WebDriver only accepts cookies for the domain of the current document,
+    /// so navigate the engine to the target origin first.
+    pub async fn push_cookies_to(&self, engine: &dyn HeadlessEngine) -> Result<()> {
+        let cookies = self.export_cookies();
+        if cookies.is_empty() {
+            return Ok(());
+        }
+        let envelope = MigrationEnvelope {
+            // The interceptor is not an engine; label the snapshot with the
+            // engine it is destined for.
+            source_engine: engine.kind(),
+            captured_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| u64::try_from(now.as_millis()).unwrap_or(u64::MAX)),
+            current_url: None,
+            cookies,
+            local_storage: Vec::new(),
+            local_storage_coerced: 0,
+            local_storage_skipped: 0,
+            cookies_skipped: 0,
+        };
+        engine
+            .import_state(envelope)
+            .await
+            .with_context(|| format!("failed to push cookies into {}", engine.instance_id()))
+    }
+
+    /// Copy `engine`'s session cookies into the client's store; returns how
+    /// many were accepted. Other captured state is ignored.
+    pub async fn pull_cookies_from(&self, engine: &dyn HeadlessEngine) -> Result<usize> {
+        let state = engine
+            .extract_state()
+            .await
+            .with_context(|| format!("failed to pull cookies from {}", engine.instance_id()))?;
+        Ok(self.import_cookies(&state.cookies))
+    }
+
+    /// GET `url` as text.
     pub async fn get_text(&self, url: &str) -> Result<String> {
-        let response = self.client.get(url).send().await?;
-        Ok(response.text().await?)
+        Ok(self.get(url).await?.body)
+    }
+
+    /// GET `url`, following redirects and keeping the status, final URL and
+    /// cookies set along the way alongside the body. Waits for the host's
+    /// rate limit and then a concurrency permit, which is held until the body
+    /// has been read.
+    pub async fn get(&self, url: &str) -> Result<FetchResponse> {
+        if let Some(limiter) = &self.rate_limiter {
+            // Unparseable URLs skip the limiter; the request reports them.
+            if let Some(host) = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)) {
+                limiter.acquire(&host).await;
+            }
+        }
+        let _permit = match &self.concurrency {
+            Some(semaphore) => Some(semaphore.acquire().await.context("request concurrency limiter closed")?),
+            None => None,
+        };
+        let mut current = Url::parse(url).with_context(|| format!("invalid URL `{url}`"))?;
+        let mut cookies = Vec::new();
+        let mut redirects = 0;
+        loop {
+            let response = self.client.get(current.clone()).send().await?;
+            cookies.extend(response_cookies(&response));
+            let Some(next) = redirect_target(&response) else {
+                let status = response.status().as_u16();
+                let body = response.text().await?;
+                return Ok(FetchResponse {
+                    status,
+                    url: current.to_string(),
+                    cookies,
+                    body,
+                });
+            };
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                bail!("too many redirects fetching {url}");
+            }
+            current = next;
+        }
+    }
+}
+
+/// `response`'s `Set-Cookie` headers, parsed against the URL that sent them.
+fn response_cookies(response: &Response) -> Vec<MigrationCookie> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .filter_map(|header| parse_set_cookie(header, response.url()))
+        .collect()
+}
+
+/// Where a redirect `response` points, resolved against its URL; `None` for
+/// other responses and redirects without a usable `Location`.
+fn redirect_target(response: &Response) -> Option<Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+    response.url().join(location).ok()
+}
+
+/// Headers every request carries so the server sees `identity`, not reqwest.
+fn default_headers(identity: &BrowserIdentity) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        USER_AGENT,
+        HeaderValue::from_str(&identity.user_agent)
+            .with_context(|| format!("identity {} has an invalid user_agent", identity.name))?,
+    );
+    headers.insert(
+        ACCEPT_LANGUAGE,
+        HeaderValue::from_str(&identity.accept_language)
+            .with_context(|| format!("identity {} has an invalid accept_language", identity.name))?,
+    );
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NetworkInterceptor;
+    use crate::stealth::identity::BrowserIdentity;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one request, replying with its header block as the body.
+    async fn echo_headers_once() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind echo server");
+        let addr = listener.local_addr().expect("echo server addr");
+        tokio::spawn(async move {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                buf.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.write_all(&buf).await;
+        });
+        format!("http://{addr}/")
+    }
+
+    /// Answer `count` requests with an empty 200, one connection each.
+    async fn serve_ok(count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind server");
+        let addr = listener.local_addr().expect("server addr");
+        tokio::spawn(async move {
+            for _ in 0..count {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 1024];
+                    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut chunk).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                    }
+                    let _ = stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                        .await;
+                });
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test]
+    async fn requests_to_a_host_are_throttled_to_the_configured_rate() {
+        let interceptor = NetworkInterceptor::builder(BrowserIdentity::default())
+            .max_concurrent_requests(2)
+            .per_host_rate(10.0, 1)
+            .build()
+            .expect("interceptor should build");
+        let url = serve_ok(4).await;
+
+        let start = std::time::Instant::now();
+        let mut fetches = tokio::task::JoinSet::new();
+        for _ in 0..4 {
+            // Clones share the limits.
+            let (interceptor, url) = (interceptor.clone(), url.clone());
+            fetches.spawn(async move { interceptor.get_text(&url).await });
+        }
+        while let Some(result) = fetches.join_next().await {
+            result.expect("fetch task").expect("request should succeed");
+        }
+        // One request immediately, then one per 100ms.
+        let elapsed = start.elapsed();
+        assert!(elapsed >= std::time::Duration::from_millis(290), "finished too fast: {elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn get_parses_set_cookie_attributes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind server");
+        let addr = listener.local_addr().expect("server addr");
+        tokio::spawn(async move {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut chunk = [0u8; 1024];
+            let _ = stream.read(&mut chunk).await;
+            let _ = stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\n\
+                      Set-Cookie: session=abc123; Path=/; HttpOnly; SameSite=Strict\r\n\
+                      Set-Cookie: theme=dark; Path=/app; Max-Age=3600\r\n\
+                      Set-Cookie: elsewhere=1; Domain=example.org\r\n\
+                      Content-Length: 2\r\nConnection: close\r\n\r\nok",
+                )
+                .await;
+        });
+        let interceptor = NetworkInterceptor::new(BrowserIdentity::default()).expect("interceptor should build");
+
+        let response = interceptor
+            .get(&format!("http://{addr}/login"))
+            .await
+            .expect("request should succeed");
+        assert_eq!((response.status, response.body.as_str()), (200, "ok"));
+        assert_eq!(response.cookies.len(), 2, "a cookie for another domain is dropped: {:?}", response.cookies);
+
+        let session = &response.cookies[0];
+        assert_eq!((session.name.as_str(), session.value.as_str()), ("session", "abc123"));
+        assert_eq!(session.domain.as_deref(), Some("127.0.0.1"));
+        assert_eq!(session.path.as_deref(), Some("/"));
+        assert_eq!((session.http_only, session.secure), (Some(true), None));
+        assert_eq!(session.same_site.as_deref(), Some("Strict"));
+        assert_eq!(session.expiry, None);
+
+        let theme = &response.cookies[1];
+        assert_eq!(theme.path.as_deref(), Some("/app"));
+        assert!(theme.expiry.is_some());
+
+        let jar = response.cookie_jar();
+        assert_eq!(jar.get("session"), Some("abc123"));
+        assert_eq!(jar.get("theme"), Some("dark"));
+        assert_eq!(interceptor.export_cookies().len(), 2, "the client's store keeps them too");
+    }
+
+    #[tokio::test]
+    async fn get_keeps_cookies_set_by_redirects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind server");
+        let addr = listener.local_addr().expect("server addr");
+        tokio::spawn(async move {
+            let hops: [&[u8]; 2] = [
+                b"HTTP/1.1 302 Found\r\n\
+                  Set-Cookie: session=abc123; Path=/; HttpOnly\r\n\
+                  Location: /home\r\n\
+                  Content-Length: 0\r\nConnection: close\r\n\r\n",
+                b"HTTP/1.1 200 OK\r\n\
+                  Set-Cookie: theme=dark; Path=/\r\n\
+                  Content-Length: 4\r\nConnection: close\r\n\r\nhome",
+            ];
+            for hop in hops {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut chunk = [0u8; 1024];
+                let _ = stream.read(&mut chunk).await;
+                let _ = stream.write_all(hop).await;
+            }
+        });
+        let interceptor = NetworkInterceptor::new(BrowserIdentity::default()).expect("interceptor should build");
+
+        let response = interceptor
+            .get(&format!("http://{addr}/login"))
+            .await
+            .expect("request should succeed");
+        assert_eq!((response.status, response.body.as_str()), (200, "home"));
+        assert_eq!(response.url, format!("http://{addr}/home"));
+        let names: Vec<_> = response.cookies.iter().map(|cookie| cookie.name.as_str()).collect();
+        assert_eq!(names, ["session", "theme"], "the redirect's cookie is kept");
+        assert_eq!(response.cookies[0].http_only, Some(true));
+        assert_eq!(interceptor.export_cookies().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn requests_carry_identity_headers() {
+        let identity = BrowserIdentity {
+            name: "test-identity".into(),
+            user_agent: "PneumaTest/1.0 (X11; Linux x86_64)".into(),
+            accept_language: "de-DE,de;q=0.8".into(),
+        };
+        let interceptor = NetworkInterceptor::new(identity).expect("interceptor should build");
+
+        let echoed = interceptor
+            .get_text(&echo_headers_once().await)
+            .await
+            .expect("request should succeed")
+            .to_ascii_lowercase();
+
+        assert!(echoed.contains("user-agent: pneumatest/1.0 (x11; linux x86_64)\r\n"), "{echoed}");
+        assert!(echoed.contains("accept-language: de-de,de;q=0.8\r\n"), "{echoed}");
+    }
+
+    #[test]
+    fn rejects_identity_with_invalid_header_value() {
+        let identity = BrowserIdentity {
+            user_agent: "bad\nagent".into(),
+            ..BrowserIdentity::default()
+        };
+        assert!(NetworkInterceptor::new(identity).is_err());
+    }
+
+    #[tokio::test]
+    async fn imported_cookies_are_sent_with_requests() {
+        let interceptor = NetworkInterceptor::new(BrowserIdentity::default()).expect("interceptor should build");
+        let url = echo_headers_once().await;
+        let host = url.trim_start_matches("http://").split(':').next().unwrap().to_string();
+        let accepted = interceptor.import_cookies(&[pneuma_engines::MigrationCookie {
+            name: "session".into(),
+            value: "from-browser".into(),
+            domain: Some(host),
+            path: Some("/".into()),
+            secure: None,
+            http_only: None,
+            expiry: None,
+            same_site: None,
+        }]);
+        assert_eq!(accepted, 1);
+
+        let echoed = interceptor.get_text(&url).await.expect("request should succeed").to_ascii_lowercase();
+        assert!(echoed.contains("cookie: session=from-browser\r\n"), "{echoed}");
+        assert_eq!(interceptor.export_cookies().len(), 1);
     }
 }
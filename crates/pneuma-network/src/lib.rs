@@ -1,5 +1,9 @@
+pub mod cookie_bridge;
 pub mod cookie_jar;
 pub mod interceptor;
+pub mod rate_limit;
 pub mod stealth;
 
-pub use interceptor::NetworkInterceptor;
+pub use cookie_bridge::SharedCookieStore;
+pub use interceptor::{FetchResponse, NetworkInterceptor, NetworkInterceptorBuilder};
+pub use rate_limit::RateLimiter;
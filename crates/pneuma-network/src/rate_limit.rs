@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket per host: each host may send `burst` requests at once, then
+/// `per_second` on average. Waiters reserve their token before sleeping, so
+/// they are admitted in arrival order and never more often than the rate.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative while requests are waiting on tokens not yet refilled.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// `per_second` must be positive; a `burst` of 0 is treated as 1.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        assert!(per_second > 0.0, "rate limit must be positive, got {per_second}");
+        Self {
+            per_second,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn per_second(&self) -> f64 {
+        self.per_second
+    }
+
+    /// Wait until a request to `host` is allowed.
+    pub async fn acquire(&self, host: &str) {
+        let wait = self.reserve(host, Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token for `host` and return how long until it is refilled.
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bucket = buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.refilled_at = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn spaces_requests_per_host_after_the_burst() {
+        let limiter = RateLimiter::new(2.0, 2);
+        let start = Instant::now();
+        let waits: Vec<Duration> = (0..4).map(|_| limiter.reserve("a.example", start)).collect();
+        assert_eq!(
+            waits,
+            [Duration::ZERO, Duration::ZERO, Duration::from_millis(500), Duration::from_secs(1)]
+        );
+        assert_eq!(limiter.reserve("b.example", start), Duration::ZERO, "hosts have their own buckets");
+
+        // By 1.5s both waiters have been let through and one token is back.
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(limiter.reserve("a.example", later), Duration::ZERO);
+        assert_eq!(limiter.reserve("a.example", later), Duration::from_millis(500));
+    }
+}
@@ -1,9 +1,96 @@
 pub mod chrome_120;
 pub mod firefox_121;
 
+/// Tokens a UA string carries the browser's major version after.
+const VERSION_MARKERS: [&str; 3] = ["Chrome/", "Firefox/", "rv:"];
+
 #[derive(Debug, Clone, Copy)]
 pub struct BrowserProfile {
     pub id: &'static str,
     pub user_agent: &'static str,
     pub platform: &'static str,
+    /// Browser major version `user_agent` was captured from.
+    pub major_version: u32,
+}
+
+impl BrowserProfile {
+    /// Every profile shipped with the crate.
+    pub fn bundled() -> [BrowserProfile; 2] {
+        [chrome_120::profile(), firefox_121::profile()]
+    }
+
+    /// The bundled Chrome profile with the highest major version.
+    pub fn latest_chrome() -> BrowserProfile {
+        Self::bundled()
+            .into_iter()
+            .filter(|profile| profile.user_agent.contains("Chrome/"))
+            .max_by_key(|profile| profile.major_version)
+            .expect("a Chrome profile is bundled")
+    }
+
+    /// `user_agent` as the same browser on the same platform would send it at
+    /// `major_version`: every version token (`Chrome/`, `Firefox/`, `rv:`)
+    /// carrying this profile's major version is rewritten, the rest is kept.
+    pub fn user_agent_for_version(&self, major_version: u32) -> String {
+        let current = self.major_version.to_string();
+        let mut user_agent = self.user_agent.to_string();
+        for marker in VERSION_MARKERS {
+            let from = format!("{marker}{current}.");
+            let to = format!("{marker}{major_version}.");
+            user_agent = user_agent.replace(&from, &to);
+        }
+        user_agent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BrowserProfile;
+
+    /// Major version after each version marker present in `user_agent`.
+    fn versions_in(user_agent: &str) -> Vec<u32> {
+        super::VERSION_MARKERS
+            .iter()
+            .filter_map(|marker| {
+                let rest = &user_agent[user_agent.find(marker)? + marker.len()..];
+                let (major, _) = rest.split_once('.')?;
+                major.parse().ok()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bundled_profiles_match_their_major_version() {
+        for profile in BrowserProfile::bundled() {
+            let versions = versions_in(profile.user_agent);
+            assert!(!versions.is_empty(), "{} has no version token", profile.id);
+            assert!(versions.iter().all(|&version| version == profile.major_version), "{}", profile.id);
+        }
+    }
+
+    #[test]
+    fn latest_chrome_is_the_newest_bundled_chrome() {
+        let latest = BrowserProfile::latest_chrome();
+        assert!(latest.user_agent.contains("Chrome/"));
+        assert_eq!(latest.id, "chrome-120-windows");
+    }
+
+    #[test]
+    fn bumped_user_agents_stay_well_formed_and_on_their_platform() {
+        let chrome = super::chrome_120::profile().user_agent_for_version(131);
+        assert_eq!(
+            chrome,
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Chrome/131.0.0.0 Safari/537.36"
+        );
+
+        let firefox = super::firefox_121::profile();
+        let bumped = firefox.user_agent_for_version(133);
+        assert_eq!(versions_in(&bumped), [133, 133], "Firefox/ and rv: move together: {bumped}");
+        assert!(bumped.starts_with("Mozilla/5.0 (X11; Linux x86_64; rv:133.0)"));
+        assert!(bumped.contains(firefox.platform));
+
+        for profile in BrowserProfile::bundled() {
+            assert_eq!(profile.user_agent_for_version(profile.major_version), profile.user_agent);
+        }
+    }
 }